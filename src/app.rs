@@ -1,26 +1,65 @@
 use crate::background::{
-    load_commits_task, rewrite_commit_task, rollback_changes_task, BackgroundMessage,
+    load_commits_task, push_branches_task, restore_backup_task, rewrite_commits_task,
+    rollback_changes_task, scan_blobs_task, scan_secrets_task, BackgroundMessage,
+};
+use crate::git::commit_map::MapFormat;
+use crate::git::secrets::scrub_rule;
+use crate::git::{
+    delete_backup, discard_original_refs, export_commit_map, finish_operation, get_current_branch,
+    get_user_identity, list_backups, list_refs, pending_operation, preflight, push_targets,
+    recover_operation, GitRepositoryImpl,
+};
+use crate::models::edit::{combine_messages, queue_edit};
+use crate::models::replace::replace_message;
+use crate::models::secret::findings_to_json;
+use crate::models::time::parse_shift;
+use crate::models::trailer::add_trailer;
+use crate::models::{
+    CommitInfo, CommitTime, Journal, LogEntry, LogFilter, LogLevel, MessageChange, MessageReplace,
+    PendingEdit, PreflightReport, PreviewData, ReplaceScope, RewriteOptions, SignatureEdit,
+    TimeEdit, Trailer,
+};
+use crate::ui::editor_panel::{IdentityForm, TrailerForm};
+use crate::ui::{
+    backups_panel, blobs_panel, commits_list, editor_panel, logs_panel, main_window,
+    preflight_modal, preview_modal, purge_panel, push_modal, recovery_modal, refs_panel,
+    replace_panel, scrub_panel, secrets_panel,
 };
-use crate::git::{get_current_branch, GitRepositoryImpl};
-use crate::models::{CommitInfo, LogEntry, LogFilter, LogLevel, PreviewData};
-use crate::ui::{commits_list, editor_panel, logs_panel, main_window, preview_modal};
 use eframe::egui;
 use std::path::PathBuf;
 use std::sync::mpsc;
 
+/// How many of the largest blobs a history scan lists.
+const BLOB_SCAN_LIMIT: usize = 200;
+
 pub struct CommitRewriterApp {
     pub repo_path: Option<PathBuf>,
     pub current_branch: Option<String>,
     pub modify_all_branches: bool,
+    pub rewrite_options: RewriteOptions,
 
     pub commits: Vec<CommitInfo>,
     pub selected_index: Option<usize>,
+    pub range_end: Option<usize>,
     pub commits_limit: usize,
     pub commits_loaded: usize,
     pub loading_more: bool,
     pub has_more_commits: bool,
 
     pub new_message: String,
+    pub identity_form: IdentityForm,
+    pub trailer_form: TrailerForm,
+    pub pending_edits: Vec<PendingEdit>,
+    /// Hashes in their loaded order, kept while commits are being moved.
+    pub original_order: Option<Vec<String>>,
+    pub replace_form: MessageReplace,
+    pub replace_preview: Option<Vec<MessageChange>>,
+    pub purge_form: purge_panel::PurgeForm,
+    pub blob_form: blobs_panel::BlobForm,
+    pub scrub_form: scrub_panel::ScrubForm,
+    pub secret_form: secrets_panel::SecretForm,
+    pub ref_form: refs_panel::RefForm,
+    pub backup_form: backups_panel::BackupForm,
 
     pub logs: Vec<LogEntry>,
     pub log_filter: LogFilter,
@@ -37,6 +76,22 @@ pub struct CommitRewriterApp {
 
     pub show_preview_modal: bool,
     pub preview_data: Option<PreviewData>,
+    /// An operation a previous run left unfinished, awaiting a decision.
+    pub pending_journal: Option<Journal>,
+    /// A rewrite held back until the user reads the pre-flight findings.
+    pub pending_rewrite: Option<PendingRewrite>,
+    /// Rewritten branches offered for a force-push after confirming.
+    pub push_form: Option<push_modal::PushForm>,
+}
+
+/// The arguments of a rewrite waiting on the pre-flight dialog.
+pub struct PendingRewrite {
+    pub report: PreflightReport,
+    pub edits: Vec<PendingEdit>,
+    pub order: Vec<String>,
+    pub options: RewriteOptions,
+    /// The user ticked that the diverging remote branches are expected.
+    pub acknowledged: bool,
 }
 
 impl Default for CommitRewriterApp {
@@ -46,15 +101,33 @@ impl Default for CommitRewriterApp {
             repo_path: None,
             current_branch: None,
             modify_all_branches: true,
+            rewrite_options: RewriteOptions {
+                rewrite_tags: true,
+                copy_notes: true,
+                ..Default::default()
+            },
 
             commits: Vec::new(),
             selected_index: None,
+            range_end: None,
             commits_limit: 50,
             commits_loaded: 0,
             loading_more: false,
             has_more_commits: true,
 
             new_message: String::new(),
+            identity_form: IdentityForm::default(),
+            trailer_form: TrailerForm::default(),
+            pending_edits: Vec::new(),
+            original_order: None,
+            replace_form: MessageReplace::default(),
+            replace_preview: None,
+            purge_form: purge_panel::PurgeForm::default(),
+            blob_form: blobs_panel::BlobForm::default(),
+            scrub_form: scrub_panel::ScrubForm::default(),
+            secret_form: secrets_panel::SecretForm::default(),
+            ref_form: refs_panel::RefForm::default(),
+            backup_form: backups_panel::BackupForm::default(),
 
             logs: Vec::new(),
             log_filter: LogFilter::All,
@@ -71,6 +144,9 @@ impl Default for CommitRewriterApp {
 
             show_preview_modal: false,
             preview_data: None,
+            pending_journal: None,
+            pending_rewrite: None,
+            push_form: None,
         };
 
         // try to use current directory if it's a git repo
@@ -78,6 +154,7 @@ impl Default for CommitRewriterApp {
             if GitRepositoryImpl::is_valid_repo(&dir) {
                 app.repo_path = Some(dir.clone());
                 app.add_log(&format!("Initialized with directory: {}", dir.display()));
+                app.check_pending_operation();
             } else {
                 app.add_log("Select a Git repository to get started");
             }
@@ -102,9 +179,141 @@ impl CommitRewriterApp {
     }
 
     pub fn load_commits_async(&mut self, ctx: egui::Context) {
+        self.refresh_refs();
+        self.refresh_backups();
         self.load_commits_async_with_limit(ctx, self.commits_limit, 0, true);
     }
 
+    /// Reloads the refs offered by the ref picker.
+    fn refresh_refs(&mut self) {
+        let Some(path) = self.repo_path.as_ref() else {
+            return;
+        };
+        match list_refs(path) {
+            Ok(refs) => self.ref_form.available = refs,
+            Err(e) => {
+                self.add_log_typed(&format!("❌ Failed to list refs: {}", e), LogLevel::Error)
+            }
+        }
+    }
+
+    /// Looks for an operation an earlier run didn't finish.
+    fn check_pending_operation(&mut self) {
+        let Some(path) = self.repo_path.as_ref() else {
+            return;
+        };
+        match pending_operation(path) {
+            Ok(Some(journal)) => {
+                self.add_log_typed(
+                    &format!(
+                        "⚠️ Found an unfinished operation: {} ({})",
+                        journal.operation, journal.started
+                    ),
+                    LogLevel::Warning,
+                );
+                self.pending_journal = Some(journal);
+            }
+            Ok(None) => self.pending_journal = None,
+            Err(e) => self.add_log_typed(
+                &format!("❌ Failed to read the operation journal: {:#}", e),
+                LogLevel::Error,
+            ),
+        }
+    }
+
+    fn recover_pending_operation(&mut self, complete: bool, ctx: egui::Context) {
+        let Some(path) = self.repo_path.clone() else {
+            return;
+        };
+        match recover_operation(&path, complete) {
+            Ok(moved) => {
+                let action = if complete { "Completed" } else { "Rolled back" };
+                self.add_log_typed(
+                    &format!(
+                        "✅ {} the unfinished operation ({} ref(s) moved)",
+                        action, moved
+                    ),
+                    LogLevel::Success,
+                );
+                self.pending_journal = None;
+                self.load_commits_async(ctx);
+            }
+            Err(e) => self.add_log_typed(&format!("❌ Recovery failed: {:#}", e), LogLevel::Error),
+        }
+    }
+
+    fn refresh_backups(&mut self) {
+        let Some(path) = self.repo_path.as_ref() else {
+            return;
+        };
+        match list_backups(path) {
+            Ok(backups) => self.backup_form.backups = backups,
+            Err(e) => self.add_log_typed(
+                &format!("❌ Failed to list backups: {}", e),
+                LogLevel::Error,
+            ),
+        }
+    }
+
+    pub fn restore_backup_async(&mut self, id: String, ctx: egui::Context) {
+        let Some(path) = self.repo_path.clone() else {
+            self.add_log("❌ Repository not selected");
+            return;
+        };
+
+        self.is_processing = true;
+        self.show_progress = true;
+        self.progress = 0.1;
+
+        let (tx, rx) = mpsc::channel();
+        self.message_receiver = Some(rx);
+
+        let ctx_clone = ctx.clone();
+        std::thread::spawn(move || {
+            restore_backup_task(path, id, tx);
+            ctx_clone.request_repaint();
+        });
+
+        ctx.request_repaint();
+    }
+
+    pub fn push_branches_async(&mut self, ctx: egui::Context) {
+        let (Some(path), Some(form)) = (self.repo_path.clone(), self.push_form.as_ref()) else {
+            return;
+        };
+        let targets = form.targets.clone();
+
+        self.is_processing = true;
+        self.show_progress = true;
+        self.progress = 0.1;
+
+        let (tx, rx) = mpsc::channel();
+        self.message_receiver = Some(rx);
+
+        let ctx_clone = ctx.clone();
+        std::thread::spawn(move || {
+            push_branches_task(path, targets, tx);
+            ctx_clone.request_repaint();
+        });
+
+        ctx.request_repaint();
+    }
+
+    fn delete_backup(&mut self, id: &str) {
+        let Some(path) = self.repo_path.as_ref() else {
+            return;
+        };
+        match delete_backup(path, id) {
+            Ok(count) => self.add_log(&format!("🗑 Deleted backup {} ({} ref(s))", id, count)),
+            Err(e) => self.add_log_typed(
+                &format!("❌ Failed to delete backup {}: {}", id, e),
+                LogLevel::Error,
+            ),
+        }
+        self.backup_form.confirm_delete = None;
+        self.refresh_backups();
+    }
+
     pub fn load_more_commits_async(&mut self, ctx: egui::Context) {
         if self.loading_more || self.is_processing {
             return;
@@ -147,10 +356,64 @@ impl CommitRewriterApp {
         ctx.request_repaint();
     }
 
-    pub fn rewrite_commit_async(
+    /// Runs the pre-flight checks, then starts the rewrite right away if
+    /// they found nothing, or holds it for the pre-flight dialog.
+    pub fn rewrite_commits_async(
         &mut self,
-        commit_hash: String,
-        new_message: String,
+        edits: Vec<PendingEdit>,
+        order: Vec<String>,
+        options: RewriteOptions,
+        ctx: egui::Context,
+    ) {
+        let Some(path) = self.repo_path.as_ref() else {
+            self.add_log("❌ Repository not selected");
+            return;
+        };
+
+        let mut commits: Vec<String> = order.clone();
+        for edit in &edits {
+            commits.push(edit.commit_hash.clone());
+            commits.extend(edit.squash.iter().cloned());
+        }
+
+        match preflight(path, &commits) {
+            Ok(report) if report.is_clear() => self.start_rewrite(edits, order, options, ctx),
+            Ok(report) => {
+                for error in &report.errors {
+                    self.add_log_typed(&format!("⛔ {}", error), LogLevel::Error);
+                }
+                for warning in &report.warnings {
+                    self.add_log_typed(&format!("⚠️ {}", warning), LogLevel::Warning);
+                }
+                if !report.diverging.is_empty() {
+                    self.add_log_typed(
+                        &format!(
+                            "⚠️ Already pushed; will diverge from {}",
+                            report.diverging.join(", ")
+                        ),
+                        LogLevel::Warning,
+                    );
+                }
+                self.pending_rewrite = Some(PendingRewrite {
+                    report,
+                    edits,
+                    order,
+                    options,
+                    acknowledged: false,
+                });
+            }
+            Err(e) => self.add_log_typed(
+                &format!("❌ Pre-flight checks failed: {:#}", e),
+                LogLevel::Error,
+            ),
+        }
+    }
+
+    fn start_rewrite(
+        &mut self,
+        edits: Vec<PendingEdit>,
+        order: Vec<String>,
+        mut options: RewriteOptions,
         ctx: egui::Context,
     ) {
         let path = match self.repo_path.as_ref() {
@@ -168,10 +431,22 @@ impl CommitRewriterApp {
 
         let modify_all = self.modify_all_branches;
         let branch_name = self.current_branch.clone();
+        options.ref_selection = self.ref_form.active_selection();
 
-        self.add_log(&format!("Starting commit rewrite {}...", &commit_hash[..8]));
-        if modify_all {
-            self.add_log("⚠️ Changes will be applied to ALL branches");
+        self.add_log(&format!("Starting rewrite of {} commit(s)...", edits.len()));
+        if let Some(selection) = &options.ref_selection {
+            let count = selection
+                .resolve(&self.ref_form.available)
+                .map_or(0, |refs| refs.len());
+            self.add_log(&format!(
+                "📌 Changes will be applied to {} picked ref(s)",
+                count
+            ));
+        } else if modify_all {
+            self.add_log_typed(
+                "⚠️ Changes will be applied to ALL branches",
+                LogLevel::Warning,
+            );
         } else if let Some(ref branch) = branch_name {
             self.add_log(&format!(
                 "📌 Changes will be applied only to branch: {}",
@@ -188,13 +463,658 @@ impl CommitRewriterApp {
 
         let ctx_clone = ctx.clone();
         std::thread::spawn(move || {
-            rewrite_commit_task(path, commit_hash, new_message, modify_all, branch_name, tx);
+            rewrite_commits_task(path, edits, order, modify_all, branch_name, options, tx);
             ctx_clone.request_repaint();
         });
 
         ctx.request_repaint();
     }
 
+    /// The selected commits as an inclusive index range, newest first.
+    pub fn selected_range(&self) -> Option<(usize, usize)> {
+        self.selected_index.map(|index| {
+            let end = self.range_end.unwrap_or(index);
+            (index.min(end), index.max(end))
+        })
+    }
+
+    fn select_commit(&mut self, index: usize) {
+        self.selected_index = Some(index);
+        self.range_end = None;
+
+        let commit = &self.commits[index];
+        let queued = self
+            .pending_edits
+            .iter()
+            .find(|edit| edit.commit_hash == commit.hash);
+
+        self.new_message = queued
+            .and_then(|edit| edit.new_message.clone())
+            .unwrap_or_else(|| commit.full_message().to_string());
+        let (author, author_time, committer, committer_time) = match queued {
+            Some(edit) => (
+                edit.author.apply(&commit.author),
+                edit.author
+                    .apply_time(commit.author_time)
+                    .unwrap_or(commit.author_time),
+                edit.committer.apply(&commit.committer),
+                edit.committer
+                    .apply_time(commit.committer_time)
+                    .unwrap_or(commit.committer_time),
+            ),
+            None => (
+                commit.author.clone(),
+                commit.author_time,
+                commit.committer.clone(),
+                commit.committer_time,
+            ),
+        };
+        self.identity_form = IdentityForm {
+            author,
+            author_date: author_time.format(),
+            committer,
+            committer_date: committer_time.format(),
+            ..std::mem::take(&mut self.identity_form)
+        };
+    }
+
+    /// Author and committer changes made in the identity form, relative to
+    /// the selected commit.
+    fn identity_edits(&self, index: usize) -> anyhow::Result<(SignatureEdit, SignatureEdit)> {
+        let commit = &self.commits[index];
+        let form = &self.identity_form;
+
+        let author_time = CommitTime::parse(&form.author_date)?;
+        let committer_time = CommitTime::parse(&form.committer_date)?;
+
+        Ok((
+            SignatureEdit::diff(&commit.author, &form.author).with_time(
+                (author_time != commit.author_time).then_some(TimeEdit::Set(author_time)),
+            ),
+            SignatureEdit::diff(&commit.committer, &form.committer).with_time(
+                (committer_time != commit.committer_time).then_some(TimeEdit::Set(committer_time)),
+            ),
+        ))
+    }
+
+    fn queue_selected_edit(&mut self) {
+        if let Some(index) = self.selected_index {
+            let (author, committer) = match self.identity_edits(index) {
+                Ok(edits) => edits,
+                Err(e) => {
+                    self.add_log_typed(&format!("❌ {}", e), LogLevel::Error);
+                    return;
+                }
+            };
+            let commit = &self.commits[index];
+
+            let mut edit = PendingEdit::new(commit.hash.clone(), commit.short_hash.clone())
+                .with_author(author)
+                .with_committer(committer);
+            if self.new_message.trim_end() != commit.full_message() {
+                edit = edit.with_message(self.new_message.clone());
+            }
+
+            if edit.is_empty() {
+                self.add_log(&format!("ℹ️ No changes to queue for {}", commit.short_hash));
+                return;
+            }
+
+            let short_hash = edit.short_hash.clone();
+            queue_edit(&mut self.pending_edits, edit);
+            self.add_log(&format!(
+                "📦 Queued edit for {} ({} in queue)",
+                short_hash,
+                self.pending_edits.len()
+            ));
+        }
+    }
+
+    fn queue_identity_for_range(&mut self) {
+        let (Some(index), Some((start, end))) = (self.selected_index, self.selected_range()) else {
+            return;
+        };
+
+        let (author, committer) = match self.identity_edits(index) {
+            Ok(edits) => edits,
+            Err(e) => {
+                self.add_log_typed(&format!("❌ {}", e), LogLevel::Error);
+                return;
+            }
+        };
+        if author.is_empty() && committer.is_empty() {
+            self.add_log_typed(
+                "⚠️ Change the author or committer fields first",
+                LogLevel::Warning,
+            );
+            return;
+        }
+
+        for i in start..=end {
+            let commit = &self.commits[i];
+            let edit = PendingEdit::new(commit.hash.clone(), commit.short_hash.clone())
+                .with_author(author.clone())
+                .with_committer(committer.clone());
+            queue_edit(&mut self.pending_edits, edit);
+        }
+
+        self.add_log(&format!(
+            "📦 Queued identity change for {} commits ({} in queue)",
+            end - start + 1,
+            self.pending_edits.len()
+        ));
+    }
+
+    fn queue_date_shift_for_range(&mut self) {
+        let Some((start, end)) = self.selected_range() else {
+            return;
+        };
+
+        let seconds = match parse_shift(&self.identity_form.shift) {
+            Ok(seconds) => seconds,
+            Err(e) => {
+                self.add_log_typed(&format!("❌ {}", e), LogLevel::Error);
+                return;
+            }
+        };
+        let shift = SignatureEdit::default().with_time(Some(TimeEdit::Shift(seconds)));
+
+        // every date, as already queued, must stay representable once shifted
+        let mut overflow = None;
+        'commits: for commit in &self.commits[start..=end] {
+            let queued = self
+                .pending_edits
+                .iter()
+                .find(|edit| edit.commit_hash == commit.hash);
+            let times = [
+                (
+                    self.identity_form.shift_author,
+                    commit.author_time,
+                    queued.map(|e| &e.author),
+                ),
+                (
+                    self.identity_form.shift_committer,
+                    commit.committer_time,
+                    queued.map(|e| &e.committer),
+                ),
+            ];
+            for (shifting, time, edit) in times {
+                let result = match edit {
+                    Some(edit) => edit.apply_time(time),
+                    None => Ok(time),
+                }
+                .and_then(|time| time.shifted(seconds));
+                if let (true, Err(e)) = (shifting, result) {
+                    overflow = Some(format!("❌ {} ({})", e, commit.short_hash));
+                    break 'commits;
+                }
+            }
+        }
+        if let Some(message) = overflow {
+            self.add_log_typed(&message, LogLevel::Error);
+            return;
+        }
+
+        for i in start..=end {
+            let commit = &self.commits[i];
+            let mut edit = PendingEdit::new(commit.hash.clone(), commit.short_hash.clone());
+            if self.identity_form.shift_author {
+                edit = edit.with_author(shift.clone());
+            }
+            if self.identity_form.shift_committer {
+                edit = edit.with_committer(shift.clone());
+            }
+            queue_edit(&mut self.pending_edits, edit);
+        }
+
+        self.add_log(&format!(
+            "📦 Queued date shift of {} for {} commits ({} in queue)",
+            self.identity_form.shift.trim(),
+            end - start + 1,
+            self.pending_edits.len()
+        ));
+
+        // refresh the form so it shows the shifted dates
+        if let Some(index) = self.selected_index {
+            let range_end = self.range_end;
+            self.select_commit(index);
+            self.range_end = range_end;
+        }
+    }
+
+    fn queue_drop_for_range(&mut self) {
+        let Some((start, end)) = self.selected_range() else {
+            return;
+        };
+
+        for i in start..=end {
+            let commit = &self.commits[i];
+            let edit = PendingEdit::new(commit.hash.clone(), commit.short_hash.clone()).with_drop();
+            queue_edit(&mut self.pending_edits, edit);
+        }
+
+        self.add_log(&format!(
+            "🗑️ Queued {} commit(s) to drop ({} in queue)",
+            end - start + 1,
+            self.pending_edits.len()
+        ));
+    }
+
+    /// Swaps the selected commit with its neighbour in the list; `up` moves
+    /// it towards HEAD.
+    fn move_selected(&mut self, up: bool) {
+        let Some(index) = self.selected_index else {
+            return;
+        };
+        let Some(target) = (if up {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1).filter(|i| *i < self.commits.len())
+        }) else {
+            return;
+        };
+
+        if self.original_order.is_none() {
+            self.original_order = Some(self.commits.iter().map(|c| c.hash.clone()).collect());
+        }
+        self.commits.swap(index, target);
+        self.selected_index = Some(target);
+        self.range_end = None;
+    }
+
+    /// Hashes of commits whose position in the list changed.
+    fn moved_commits(&self) -> Vec<String> {
+        let Some(original) = &self.original_order else {
+            return Vec::new();
+        };
+        self.commits
+            .iter()
+            .zip(original)
+            .filter(|(commit, hash)| commit.hash != **hash)
+            .map(|(commit, _)| commit.hash.clone())
+            .collect()
+    }
+
+    /// The new order for the part of history that moved, oldest first: every
+    /// commit from the deepest moved one up to HEAD.
+    fn pending_order(&self) -> Vec<String> {
+        let Some(original) = &self.original_order else {
+            return Vec::new();
+        };
+        let deepest = self
+            .commits
+            .iter()
+            .zip(original)
+            .rposition(|(commit, hash)| commit.hash != *hash);
+        match deepest {
+            Some(deepest) => self.commits[..=deepest]
+                .iter()
+                .rev()
+                .map(|commit| commit.hash.clone())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The message a commit will end up with: its queued rewording, if any.
+    fn current_message(&self, index: usize) -> String {
+        let commit = &self.commits[index];
+        self.pending_edits
+            .iter()
+            .find(|edit| edit.commit_hash == commit.hash)
+            .and_then(|edit| edit.new_message.clone())
+            .unwrap_or_else(|| commit.full_message().to_string())
+    }
+
+    fn preview_replacements(&mut self) {
+        let regex = match self.replace_form.compile() {
+            Ok(regex) => regex,
+            Err(e) => {
+                self.add_log_typed(&format!("❌ {:#}", e), LogLevel::Error);
+                return;
+            }
+        };
+
+        let indices: Vec<usize> = match self.replace_form.scope {
+            ReplaceScope::SelectedRange => match self.selected_range() {
+                Some((start, end)) => (start..=end).collect(),
+                None => {
+                    self.add_log_typed("⚠️ Select a commit or range first", LogLevel::Warning);
+                    return;
+                }
+            },
+            ReplaceScope::SearchResults => {
+                commits_list::filter_commits(&self.commits, &self.search_query)
+                    .into_iter()
+                    .map(|(i, _)| i)
+                    .collect()
+            }
+        };
+
+        let changes: Vec<MessageChange> = indices
+            .iter()
+            .filter_map(|&i| {
+                let before = self.current_message(i);
+                let after = replace_message(&regex, &self.replace_form.replace, &before)?;
+                let commit = &self.commits[i];
+                Some(MessageChange {
+                    commit_hash: commit.hash.clone(),
+                    short_hash: commit.short_hash.clone(),
+                    before,
+                    after,
+                })
+            })
+            .collect();
+
+        self.add_log(&format!(
+            "🔍 {} of {} messages match /{}/",
+            changes.len(),
+            indices.len(),
+            self.replace_form.find
+        ));
+        self.replace_preview = Some(changes);
+    }
+
+    fn purge_history(&mut self, ctx: egui::Context) {
+        let options = RewriteOptions {
+            purge_paths: self.purge_form.patterns(),
+            prune_empty: self.purge_form.prune_empty,
+            ..self.rewrite_options.clone()
+        };
+        self.filter_history(options, ctx);
+    }
+
+    fn strip_blobs(&mut self, selected_only: bool, ctx: egui::Context) {
+        let options = if selected_only {
+            RewriteOptions {
+                strip_blobs: self.blob_form.selected.iter().cloned().collect(),
+                prune_empty: self.blob_form.prune_empty,
+                ..self.rewrite_options.clone()
+            }
+        } else {
+            RewriteOptions {
+                max_blob_size: Some(self.blob_form.threshold_bytes()),
+                prune_empty: self.blob_form.prune_empty,
+                ..self.rewrite_options.clone()
+            }
+        };
+        self.filter_history(options, ctx);
+    }
+
+    fn scrub_history(&mut self, ctx: egui::Context) {
+        let options = RewriteOptions {
+            replace_text: self.scrub_form.rules(),
+            replacement: self.scrub_form.replacement(),
+            ..self.rewrite_options.clone()
+        };
+        self.filter_history(options, ctx);
+    }
+
+    /// Starts a rewrite that changes the content of every commit. Queued
+    /// edits would be discarded on confirm, so they have to be applied or
+    /// cleared first.
+    fn filter_history(&mut self, options: RewriteOptions, ctx: egui::Context) {
+        if !self.pending_edits.is_empty() || !self.pending_order().is_empty() {
+            self.add_log_typed(
+                "⚠️ Apply or clear queued edits before rewriting content",
+                LogLevel::Warning,
+            );
+            return;
+        }
+        self.rewrite_commits_async(Vec::new(), Vec::new(), options, ctx);
+    }
+
+    pub fn scan_blobs_async(&mut self, ctx: egui::Context) {
+        let Some(path) = self.repo_path.clone() else {
+            self.add_log("❌ Repository not selected");
+            return;
+        };
+
+        self.is_processing = true;
+        let (tx, rx) = mpsc::channel();
+        self.message_receiver = Some(rx);
+
+        let ctx_clone = ctx.clone();
+        std::thread::spawn(move || {
+            scan_blobs_task(path, BLOB_SCAN_LIMIT, tx);
+            ctx_clone.request_repaint();
+        });
+
+        ctx.request_repaint();
+    }
+
+    pub fn scan_secrets_async(&mut self, ctx: egui::Context) {
+        let Some(path) = self.repo_path.clone() else {
+            self.add_log("❌ Repository not selected");
+            return;
+        };
+        let custom_rules = self.secret_form.custom_rules();
+
+        self.is_processing = true;
+        self.show_progress = true;
+        self.progress = 0.0;
+        let (tx, rx) = mpsc::channel();
+        self.message_receiver = Some(rx);
+
+        let ctx_clone = ctx.clone();
+        std::thread::spawn(move || {
+            scan_secrets_task(path, custom_rules, tx);
+            ctx_clone.request_repaint();
+        });
+
+        ctx.request_repaint();
+    }
+
+    fn export_commit_map(&mut self, id: &str, format: MapFormat) {
+        let Some(path) = self.repo_path.clone() else {
+            return;
+        };
+        let Some(file) = rfd::FileDialog::new()
+            .set_file_name(format!("commit-map-{}.{}", id, format.extension()))
+            .add_filter(format.extension().to_uppercase(), &[format.extension()])
+            .save_file()
+        else {
+            return;
+        };
+
+        match export_commit_map(&path, id, format).and_then(|text| Ok(std::fs::write(&file, text)?))
+        {
+            Ok(()) => self.add_log_typed(
+                &format!("💾 Exported commit map to {}", file.display()),
+                LogLevel::Success,
+            ),
+            Err(e) => self.add_log_typed(&format!("❌ Export failed: {:#}", e), LogLevel::Error),
+        }
+    }
+
+    fn export_secret_findings(&mut self) {
+        let Some(findings) = &self.secret_form.results else {
+            return;
+        };
+        let Some(file) = rfd::FileDialog::new()
+            .set_file_name("secrets.json")
+            .add_filter("JSON", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+
+        match findings_to_json(findings).and_then(|json| Ok(std::fs::write(&file, json)?)) {
+            Ok(()) => self.add_log_typed(
+                &format!(
+                    "💾 Exported {} finding(s) to {}",
+                    findings.len(),
+                    file.display()
+                ),
+                LogLevel::Success,
+            ),
+            Err(e) => self.add_log_typed(&format!("❌ Export failed: {:#}", e), LogLevel::Error),
+        }
+    }
+
+    /// Appends the files of the selected findings to the purge list,
+    /// anchored at the repository root.
+    fn purge_secret_files(&mut self) {
+        let mut patterns = self.purge_form.patterns();
+        let mut added = 0;
+        for finding in self.secret_form.selected_findings() {
+            let Some(path) = &finding.path else {
+                continue;
+            };
+            let pattern = format!("/{}", path);
+            if !patterns.contains(&pattern) {
+                patterns.push(pattern);
+                added += 1;
+            }
+        }
+        self.purge_form.patterns = patterns.join("\n");
+        self.add_log(&format!(
+            "🧹 Added {} file(s) to the purge list; review it and click Purge",
+            added
+        ));
+    }
+
+    /// Appends a replace-text rule for each selected secret.
+    fn scrub_secret_text(&mut self) {
+        let mut rules = self.scrub_form.rules();
+        let mut added = 0;
+        for finding in self.secret_form.selected_findings() {
+            let rule = scrub_rule(finding);
+            if !rules.contains(&rule) {
+                rules.push(rule);
+                added += 1;
+            }
+        }
+        self.scrub_form.rules = rules.join("\n");
+        self.add_log(&format!(
+            "🔒 Added {} secret(s) to the replace-text rules; review them and click Replace",
+            added
+        ));
+    }
+
+    fn queue_replacements(&mut self) {
+        let Some(changes) = self.replace_preview.take() else {
+            return;
+        };
+
+        for change in &changes {
+            let edit = PendingEdit::new(change.commit_hash.clone(), change.short_hash.clone())
+                .with_message(change.after.clone());
+            queue_edit(&mut self.pending_edits, edit);
+        }
+
+        self.add_log(&format!(
+            "📦 Queued {} message replacements ({} in queue)",
+            changes.len(),
+            self.pending_edits.len()
+        ));
+
+        if let Some(index) = self.selected_index {
+            let range_end = self.range_end;
+            self.select_commit(index);
+            self.range_end = range_end;
+        }
+    }
+
+    /// Queues the selected range as one squash and selects its oldest commit
+    /// so the combined message can be edited before applying.
+    fn queue_squash_for_range(&mut self) {
+        let Some((start, end)) = self.selected_range().filter(|(start, end)| end > start) else {
+            return;
+        };
+
+        // the list is newest first, so the oldest commit is at the end
+        let members: Vec<String> = (start..end)
+            .rev()
+            .map(|i| self.commits[i].hash.clone())
+            .collect();
+        if let Some(edit) = self
+            .pending_edits
+            .iter()
+            .find(|edit| members.contains(&edit.commit_hash))
+        {
+            let message = format!(
+                "⚠️ {} already has queued edits; remove them before squashing",
+                edit.short_hash
+            );
+            self.add_log_typed(&message, LogLevel::Warning);
+            return;
+        }
+
+        let messages: Vec<String> = (start..=end)
+            .rev()
+            .map(|i| self.current_message(i))
+            .collect();
+        let head = &self.commits[end];
+        let edit = PendingEdit::new(head.hash.clone(), head.short_hash.clone())
+            .with_message(combine_messages(&messages))
+            .with_squash(members);
+        let short_hash = head.short_hash.clone();
+        queue_edit(&mut self.pending_edits, edit);
+
+        self.select_commit(end);
+        self.add_log(&format!(
+            "🧩 Queued squash of {} commits into {}; edit the combined message and queue or apply",
+            end - start + 1,
+            short_hash
+        ));
+    }
+
+    fn signoff_trailer(&mut self) -> Option<Trailer> {
+        let identity = self.repo_path.as_ref().and_then(get_user_identity);
+        if identity.is_none() {
+            self.add_log_typed(
+                "⚠️ Set user.name and user.email to sign off commits",
+                LogLevel::Warning,
+            );
+        }
+        identity.map(|identity| Trailer::new("Signed-off-by", &identity.to_string()))
+    }
+
+    fn signoff_selected(&mut self) {
+        let Some(trailer) = self.signoff_trailer() else {
+            return;
+        };
+        match add_trailer(&self.new_message, trailer) {
+            Some(message) => self.new_message = message,
+            None => self.add_log("ℹ️ Already signed off"),
+        }
+    }
+
+    fn queue_signoff_for_range(&mut self) {
+        let Some((start, end)) = self.selected_range() else {
+            return;
+        };
+        let Some(trailer) = self.signoff_trailer() else {
+            return;
+        };
+
+        let mut added = 0;
+        for i in start..=end {
+            let message = self.current_message(i);
+            if let Some(message) = add_trailer(&message, trailer.clone()) {
+                let commit = &self.commits[i];
+                let edit = PendingEdit::new(commit.hash.clone(), commit.short_hash.clone())
+                    .with_message(message);
+                queue_edit(&mut self.pending_edits, edit);
+                added += 1;
+            }
+        }
+
+        self.add_log(&format!(
+            "📦 Queued sign-off for {} of {} commits ({} in queue)",
+            added,
+            end - start + 1,
+            self.pending_edits.len()
+        ));
+
+        if let Some(index) = self.selected_index {
+            let range_end = self.range_end;
+            self.select_commit(index);
+            self.range_end = range_end;
+        }
+    }
+
     pub fn rollback_changes_async(&mut self, ctx: egui::Context) {
         let path = match self.repo_path.as_ref() {
             Some(p) => p.clone(),
@@ -227,11 +1147,8 @@ impl CommitRewriterApp {
 
         if let Some(receiver) = self.message_receiver.take() {
             let mut messages = Vec::new();
-            loop {
-                match receiver.try_recv() {
-                    Ok(msg) => messages.push(msg),
-                    Err(_) => break,
-                }
+            while let Ok(msg) = receiver.try_recv() {
+                messages.push(msg);
             }
 
             for msg in messages {
@@ -251,12 +1168,16 @@ impl CommitRewriterApp {
                     BackgroundMessage::CommitsLoaded(commits) => {
                         let loaded_count = commits.len();
                         if self.loading_more {
+                            if let Some(original) = self.original_order.as_mut() {
+                                original.extend(commits.iter().map(|c| c.hash.clone()));
+                            }
                             self.commits.extend(commits);
                             self.commits_loaded = self.commits.len();
                             self.loading_more = false;
                             // If we loaded less than requested, there are no more commits
                             self.has_more_commits = loaded_count >= 50;
                         } else {
+                            self.original_order = None;
                             self.commits = commits;
                             self.commits_loaded = self.commits.len();
                             self.commits_limit = self.commits_loaded;
@@ -269,8 +1190,31 @@ impl CommitRewriterApp {
                     BackgroundMessage::CommitRewritten => {
                         commit_rewritten = true;
                     }
+                    BackgroundMessage::PushTargets(targets) => {
+                        self.push_form = Some(push_modal::PushForm {
+                            targets,
+                            results: None,
+                        });
+                        needs_repaint = true;
+                    }
+                    BackgroundMessage::Pushed(results) => {
+                        if let Some(form) = self.push_form.as_mut() {
+                            form.results = Some(results);
+                        }
+                        needs_repaint = true;
+                    }
+                    BackgroundMessage::SecretsFound(findings) => {
+                        self.secret_form.selected.clear();
+                        self.secret_form.results = Some(findings);
+                        needs_repaint = true;
+                    }
+                    BackgroundMessage::BlobsScanned(blobs) => {
+                        self.blob_form.selected.clear();
+                        self.blob_form.results = Some(blobs);
+                        needs_repaint = true;
+                    }
                     BackgroundMessage::PreviewReady(data) => {
-                        self.preview_data = Some(data);
+                        self.preview_data = Some(*data);
                         self.show_preview_modal = true;
                         needs_repaint = true;
                     }
@@ -318,31 +1262,108 @@ impl eframe::App for CommitRewriterApp {
 
         let needs_repaint = self.process_background_messages(ctx);
 
+        if let Some(pending) = self.pending_rewrite.as_mut() {
+            let result = preflight_modal::render_preflight_modal(
+                ctx,
+                &pending.report,
+                &mut pending.acknowledged,
+            );
+            if result.proceed_clicked {
+                if let Some(pending) = self.pending_rewrite.take() {
+                    self.add_log_typed("Continuing despite pre-flight warnings", LogLevel::Warning);
+                    self.start_rewrite(pending.edits, pending.order, pending.options, ctx.clone());
+                }
+            }
+            if result.cancel_clicked {
+                self.pending_rewrite = None;
+                self.add_log("Rewrite cancelled");
+            }
+        }
+
+        if let Some(form) = &self.push_form {
+            let result = push_modal::render_push_modal(ctx, form, self.is_processing);
+            if result.push_clicked {
+                self.push_branches_async(ctx.clone());
+            }
+            if result.close_clicked {
+                self.push_form = None;
+            }
+        }
+
+        if let Some(journal) = self.pending_journal.clone() {
+            let result = recovery_modal::render_recovery_modal(ctx, &journal, self.is_processing);
+            if result.complete_clicked {
+                self.recover_pending_operation(true, ctx.clone());
+            }
+            if result.rollback_clicked {
+                self.recover_pending_operation(false, ctx.clone());
+            }
+        }
+
         if self.show_preview_modal {
             if let Some(preview) = self.preview_data.clone() {
                 let result = preview_modal::render_preview_modal(ctx, &preview, self.is_processing);
 
                 if result.confirm_clicked {
-                    if self.repo_path.is_some() {
+                    if let Some(path) = self.repo_path.clone() {
                         let (tx, rx) = mpsc::channel();
                         self.message_receiver = Some(rx);
+                        let backup = preview.backup.clone();
 
                         std::thread::spawn(move || {
                             tx.send(BackgroundMessage::Log(
                                 "Cleaning up temporary refs...".to_string(),
                             ))
                             .ok();
+                            if let Err(e) = discard_original_refs(&path) {
+                                tx.send(BackgroundMessage::Error(format!(
+                                    "Failed to clean up refs/original: {:#}",
+                                    e
+                                )))
+                                .ok();
+                            }
+                            if let Err(e) = finish_operation(&path) {
+                                tx.send(BackgroundMessage::Error(format!(
+                                    "Journal error: {:#}",
+                                    e
+                                )))
+                                .ok();
+                            }
                             tx.send(BackgroundMessage::Log("✅ Changes confirmed!".to_string()))
                                 .ok();
+                            match backup.map(|id| push_targets(&path, &id)) {
+                                Some(Ok(targets)) if !targets.is_empty() => {
+                                    tx.send(BackgroundMessage::PushTargets(targets)).ok();
+                                }
+                                Some(Err(e)) => {
+                                    tx.send(BackgroundMessage::Error(format!(
+                                        "Failed to find branches to push: {:#}",
+                                        e
+                                    )))
+                                    .ok();
+                                }
+                                _ => {}
+                            }
                             tx.send(BackgroundMessage::CommitRewritten).ok();
                             tx.send(BackgroundMessage::Done).ok();
                         });
                     }
 
+                    self.pending_edits.clear();
+                    self.replace_preview = None;
+                    self.blob_form.results = None;
+                    self.blob_form.selected.clear();
+                    self.secret_form.results = None;
+                    self.secret_form.selected.clear();
+
                     self.show_preview_modal = false;
                     self.preview_data = None;
                 }
 
+                if let (Some(format), Some(id)) = (result.export_map, &preview.backup) {
+                    self.export_commit_map(id, format);
+                }
+
                 if result.cancel_clicked {
                     self.rollback_changes_async(ctx.clone());
                     self.show_preview_modal = false;
@@ -363,7 +1384,7 @@ impl eframe::App for CommitRewriterApp {
             .show(ctx, |ui| {
                 logs_panel::render_logs_panel(
                     ui,
-                    &self.logs,
+                    &mut self.logs,
                     &mut self.log_filter,
                     self.show_progress,
                     self.progress,
@@ -379,6 +1400,7 @@ impl eframe::App for CommitRewriterApp {
                 self.is_processing,
                 &mut self.show_help,
                 &mut self.modify_all_branches,
+                &mut self.rewrite_options,
                 self.current_branch.as_ref(),
             );
 
@@ -386,7 +1408,16 @@ impl eframe::App for CommitRewriterApp {
                 if let Some(path) = rfd::FileDialog::new().pick_folder() {
                     if GitRepositoryImpl::is_valid_repo(&path) {
                         self.repo_path = Some(path.clone());
+                        self.ref_form = refs_panel::RefForm::default();
+                        self.backup_form = backups_panel::BackupForm::default();
+                        self.check_pending_operation();
                         self.current_branch = get_current_branch(&path);
+                        self.pending_edits.clear();
+                        self.replace_preview = None;
+                        self.blob_form.results = None;
+                        self.blob_form.selected.clear();
+                        self.secret_form.results = None;
+                        self.secret_form.selected.clear();
                         if let Some(ref branch) = self.current_branch {
                             self.add_log(&format!(
                                 "✅ Loading commits from: {} (branch: {})",
@@ -409,20 +1440,51 @@ impl eframe::App for CommitRewriterApp {
             }
 
             ui.vertical(|ui| {
+                let moved_commits = self.moved_commits();
                 let commits_result = commits_list::render_commits_list(
                     ui,
                     &self.commits,
-                    self.selected_index,
+                    self.selected_range(),
+                    &self.pending_edits,
+                    &moved_commits,
                     &mut self.search_query,
                     self.is_processing,
                     self.loading_more,
                     self.has_more_commits,
                 );
 
-                if let Some((index, short_hash, message)) = commits_result.selected_commit {
-                    self.selected_index = Some(index);
-                    self.new_message = message.clone();
-                    self.add_log(&format!("📝 Selected commit: {} - {}", short_hash, message));
+                if let Some((index, short_hash, subject)) = commits_result.selected_commit {
+                    if commits_result.extend_selection && self.selected_index.is_some() {
+                        self.range_end = Some(index);
+                        if let Some((start, end)) = self.selected_range() {
+                            self.add_log(&format!(
+                                "📝 Selected range of {} commits",
+                                end - start + 1
+                            ));
+                        }
+                    } else {
+                        self.select_commit(index);
+                        self.add_log(&format!("📝 Selected commit: {} - {}", short_hash, subject));
+                    }
+                }
+
+                if commits_result.move_up_clicked || commits_result.move_down_clicked {
+                    self.move_selected(commits_result.move_up_clicked);
+                }
+
+                if commits_result.drop_clicked {
+                    self.queue_drop_for_range();
+                }
+
+                if commits_result.reset_order_clicked {
+                    if let Some(original) = self.original_order.take() {
+                        self.commits.sort_by_key(|commit| {
+                            original.iter().position(|hash| *hash == commit.hash)
+                        });
+                        self.selected_index = None;
+                        self.range_end = None;
+                        self.add_log("↺ Restored the original commit order");
+                    }
                 }
 
                 if commits_result.load_more_clicked {
@@ -435,26 +1497,176 @@ impl eframe::App for CommitRewriterApp {
 
                 ui.separator();
 
+                let range_len = self
+                    .selected_range()
+                    .map(|(start, end)| end - start + 1)
+                    .unwrap_or(0);
+
                 let editor_result = editor_panel::render_editor_panel(
                     ui,
                     &mut self.new_message,
+                    &mut self.identity_form,
+                    &mut self.trailer_form,
                     self.selected_index,
+                    range_len,
+                    &self.pending_edits,
                     self.is_processing,
                 );
 
+                if editor_result.queue_clicked {
+                    self.queue_selected_edit();
+                }
+
+                if editor_result.apply_identity_to_range {
+                    self.queue_identity_for_range();
+                }
+
+                if editor_result.shift_dates_clicked {
+                    self.queue_date_shift_for_range();
+                }
+
+                if editor_result.squash_clicked {
+                    self.queue_squash_for_range();
+                }
+
+                if editor_result.signoff_clicked {
+                    self.signoff_selected();
+                }
+
+                if editor_result.signoff_range_clicked {
+                    self.queue_signoff_for_range();
+                }
+
+                refs_panel::render_refs_panel(ui, &mut self.ref_form, self.is_processing);
+
+                let search_count =
+                    commits_list::filter_commits(&self.commits, &self.search_query).len();
+                let replace_result = replace_panel::render_replace_panel(
+                    ui,
+                    &mut self.replace_form,
+                    range_len,
+                    search_count,
+                    self.replace_preview.as_deref(),
+                    self.is_processing,
+                );
+
+                if replace_result.preview_clicked {
+                    self.preview_replacements();
+                }
+
+                if replace_result.queue_clicked {
+                    self.queue_replacements();
+                }
+
+                if replace_result.discard_clicked {
+                    self.replace_preview = None;
+                }
+
+                let purge_result =
+                    purge_panel::render_purge_panel(ui, &mut self.purge_form, self.is_processing);
+
+                if purge_result.purge_clicked {
+                    self.purge_history(ctx.clone());
+                }
+
+                let secrets_result = secrets_panel::render_secrets_panel(
+                    ui,
+                    &mut self.secret_form,
+                    self.repo_path.is_some(),
+                    self.is_processing,
+                );
+
+                if secrets_result.scan_clicked {
+                    self.scan_secrets_async(ctx.clone());
+                }
+
+                if secrets_result.export_clicked {
+                    self.export_secret_findings();
+                }
+
+                if secrets_result.purge_files_clicked {
+                    self.purge_secret_files();
+                }
+
+                if secrets_result.scrub_text_clicked {
+                    self.scrub_secret_text();
+                }
+
+                let scrub_result =
+                    scrub_panel::render_scrub_panel(ui, &mut self.scrub_form, self.is_processing);
+
+                if scrub_result.scrub_clicked {
+                    self.scrub_history(ctx.clone());
+                }
+
+                let blobs_result = blobs_panel::render_blobs_panel(
+                    ui,
+                    &mut self.blob_form,
+                    self.repo_path.is_some(),
+                    self.is_processing,
+                );
+
+                if blobs_result.scan_clicked {
+                    self.scan_blobs_async(ctx.clone());
+                }
+
+                if blobs_result.strip_selected_clicked {
+                    self.strip_blobs(true, ctx.clone());
+                }
+
+                if blobs_result.strip_over_clicked {
+                    self.strip_blobs(false, ctx.clone());
+                }
+
+                let backups_result = backups_panel::render_backups_panel(
+                    ui,
+                    &mut self.backup_form,
+                    self.repo_path.is_some(),
+                    self.is_processing,
+                );
+
+                if backups_result.refresh_clicked {
+                    self.refresh_backups();
+                }
+
+                if let Some(id) = backups_result.restore {
+                    self.restore_backup_async(id, ctx.clone());
+                }
+
+                if let Some(id) = backups_result.delete {
+                    self.delete_backup(&id);
+                }
+
+                if let Some((id, format)) = backups_result.export {
+                    self.export_commit_map(&id, format);
+                }
+
+                if let Some(i) = editor_result.removed_edit {
+                    let edit = self.pending_edits.remove(i);
+                    self.add_log(&format!("🗑️ Removed {} from queue", edit.short_hash));
+                }
+
+                if editor_result.clear_queue_clicked {
+                    self.pending_edits.clear();
+                    self.add_log("🗑️ Cleared queued edits");
+                }
+
                 if editor_result.apply_clicked {
-                    if let Some(index) = self.selected_index {
-                        let commit = self.commits[index].clone();
-                        let new_msg = self.new_message.clone();
-
-                        self.add_log(&format!(
-                            "🔄 Preparing to rewrite commit {}...",
-                            commit.short_hash
-                        ));
-                        self.add_log(&format!("📝 Old: {}", commit.message));
-                        self.add_log(&format!("📝 New: {}", new_msg));
-
-                        self.rewrite_commit_async(commit.hash, new_msg, ctx.clone());
+                    if self.selected_index.is_some() && !self.new_message.trim().is_empty() {
+                        self.queue_selected_edit();
+                    }
+
+                    let edits = self.pending_edits.clone();
+                    let order = self.pending_order();
+                    if edits.is_empty() && order.is_empty() {
+                        self.add_log("ℹ️ Nothing to apply");
+                    } else {
+                        for edit in &edits {
+                            self.add_log(&format!("📝 {} → {}", edit.short_hash, edit.summary()));
+                        }
+
+                        let options = self.rewrite_options.clone();
+                        self.rewrite_commits_async(edits, order, options, ctx.clone());
                     }
                 }
             });
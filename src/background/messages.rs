@@ -1,4 +1,6 @@
-use crate::models::{CommitInfo, LogLevel, PreviewData};
+use crate::models::{
+    BlobInfo, CommitInfo, LogLevel, PreviewData, PushResult, PushTarget, SecretFinding,
+};
 
 #[derive(Clone)]
 pub enum BackgroundMessage {
@@ -7,7 +9,11 @@ pub enum BackgroundMessage {
     Progress(f32),
     CommitsLoaded(Vec<CommitInfo>),
     CommitRewritten,
-    PreviewReady(PreviewData),
+    PreviewReady(Box<PreviewData>),
+    BlobsScanned(Vec<BlobInfo>),
+    SecretsFound(Vec<SecretFinding>),
+    PushTargets(Vec<PushTarget>),
+    Pushed(Vec<PushResult>),
     Error(String),
     Done,
 }
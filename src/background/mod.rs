@@ -2,4 +2,7 @@ pub mod messages;
 pub mod tasks;
 
 pub use messages::BackgroundMessage;
-pub use tasks::{load_commits_task, rewrite_commit_task, rollback_changes_task};
+pub use tasks::{
+    load_commits_task, push_branches_task, restore_backup_task, rewrite_commits_task,
+    rollback_changes_task, scan_blobs_task, scan_secrets_task,
+};
@@ -1,6 +1,10 @@
 use crate::background::BackgroundMessage;
-use crate::git::{rollback_changes, GitRepository, GitRepositoryImpl};
-use std::io::{BufRead, BufReader};
+use crate::git::operations::{find_secrets, rewrite_commits, scan_blobs};
+use crate::git::{
+    begin_operation, push_branches, recover_operation, restore_backup, rollback_changes,
+    GitRepository, GitRepositoryImpl,
+};
+use crate::models::{format_size, LogLevel, PendingEdit, PushTarget, RewriteOptions};
 use std::path::PathBuf;
 use std::sync::mpsc::Sender;
 
@@ -63,184 +67,224 @@ pub fn load_commits_task(
     tx.send(BackgroundMessage::Done).ok();
 }
 
-pub fn rewrite_commit_task(
+pub fn rewrite_commits_task(
     path: PathBuf,
-    commit_hash: String,
-    new_message: String,
+    edits: Vec<PendingEdit>,
+    order: Vec<String>,
     modify_all_branches: bool,
     branch_name: Option<String>,
+    options: RewriteOptions,
     tx: Sender<BackgroundMessage>,
 ) {
     let send_log = |msg: &str| {
         tx.send(BackgroundMessage::Log(msg.to_string())).ok();
     };
 
-    send_log("Searching for commit in repository...");
+    let operation = if !edits.is_empty() {
+        format!("Rewrite {} commit(s)", edits.len())
+    } else if !order.is_empty() {
+        format!("Reorder {} commit(s)", order.len())
+    } else {
+        "Filter history".to_string()
+    };
+    if let Err(e) = begin_operation(&path, &operation) {
+        tx.send(BackgroundMessage::Error(format!("{:#}", e))).ok();
+        return;
+    }
+
+    send_log(&format!(
+        "Searching for {} commit(s) in repository...",
+        edits.len()
+    ));
     tx.send(BackgroundMessage::Progress(0.3)).ok();
 
     send_log("Preparing to rewrite history...");
-    send_log("⚙️ Using git filter-branch...");
-    send_log("⏳ This may take a while...");
+    send_log("⚙️ Recreating edited commits and their descendants...");
     tx.send(BackgroundMessage::Progress(0.5)).ok();
 
-    let mut child = match crate::git::commands::run_git_filter_branch(
+    if !order.is_empty() {
+        send_log(&format!(
+            "↕️ Replaying {} commits in the new order...",
+            order.len()
+        ));
+    }
+    if !options.purge_paths.is_empty() {
+        send_log(&format!(
+            "🧹 Purging {} from every commit...",
+            options.purge_paths.join(", ")
+        ));
+    }
+    if let Some(max) = options.max_blob_size {
+        send_log(&format!("📦 Stripping blobs over {}...", format_size(max)));
+    }
+    if !options.strip_blobs.is_empty() {
+        send_log(&format!(
+            "📦 Stripping {} selected blob(s)...",
+            options.strip_blobs.len()
+        ));
+    }
+    if !options.replace_text.is_empty() {
+        send_log(&format!(
+            "🔒 Replacing text from {} rule(s) in every file...",
+            options.replace_text.len()
+        ));
+    }
+    if options.resign_commits {
+        send_log("🔏 Re-signing rewritten commits with user.signingkey...");
+    }
+
+    let preview_data = match rewrite_commits(
         &path,
-        &commit_hash,
-        &new_message,
+        &edits,
+        &order,
         modify_all_branches,
         branch_name.as_deref(),
+        &options,
     ) {
-        Ok(c) => c,
+        Ok(data) => data,
         Err(e) => {
-            tx.send(BackgroundMessage::Error(format!(
-                "Failed to start git filter-branch: {}",
-                e
-            )))
-            .ok();
+            // put back any refs moved before the failure
+            match recover_operation(&path, false) {
+                Ok(0) => {}
+                Ok(moved) => send_log(&format!("↩ Moved {} ref(s) back", moved)),
+                Err(e) => {
+                    tx.send(BackgroundMessage::Error(format!(
+                        "Recovery failed: {:#}",
+                        e
+                    )))
+                    .ok();
+                }
+            }
+            tx.send(BackgroundMessage::Error(format!("Rewrite failed: {:#}", e)))
+                .ok();
             return;
         }
     };
 
-    // read stdout/stderr in separate threads
-    let tx_stdout = tx.clone();
-    let tx_stderr = tx.clone();
-
-    if let Some(stdout) = child.stdout.take() {
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stdout);
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    if !line.trim().is_empty() {
-                        tx_stdout
-                            .send(BackgroundMessage::Log(format!("📝 {}", line)))
-                            .ok();
-                    }
-                }
-            }
-        });
+    tx.send(BackgroundMessage::Progress(0.9)).ok();
+    for ref_name in &preview_data.affected_commits {
+        tx.send(BackgroundMessage::LogTyped(
+            format!("Updated {}", ref_name),
+            LogLevel::Debug,
+        ))
+        .ok();
     }
-
-    if let Some(stderr) = child.stderr.take() {
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    if !line.trim().is_empty() {
-                        tx_stderr
-                            .send(BackgroundMessage::Log(format!("📝 {}", line)))
-                            .ok();
-                    }
-                }
-            }
-        });
+    for tag in &preview_data.moved_tags {
+        tx.send(BackgroundMessage::LogTyped(
+            format!("🏷 Tag {}", tag),
+            LogLevel::Debug,
+        ))
+        .ok();
     }
-
-    // send progress updates while process runs
-    let mut progress = 0.5;
-    loop {
-        std::thread::sleep(std::time::Duration::from_millis(500));
-        progress += 0.05;
-        if progress > 0.9 {
-            progress = 0.9;
-        }
-        tx.send(BackgroundMessage::Progress(progress)).ok();
-
-        match child.try_wait() {
-            Ok(Some(status)) => {
-                tx.send(BackgroundMessage::Progress(0.9)).ok();
-
-                if !status.success() {
-                    tx.send(BackgroundMessage::Error(
-                        "git filter-branch failed (see logs above)".to_string(),
-                    ))
-                    .ok();
-                    return;
-                }
-
-                send_log("✅ git filter-branch completed successfully");
-                break;
-            }
-            Ok(None) => {
-                continue;
-            }
-            Err(e) => {
-                tx.send(BackgroundMessage::Error(format!(
-                    "Error checking status: {}",
-                    e
-                )))
-                .ok();
-                return;
-            }
-        }
+    for line in &preview_data.hash_refs {
+        tx.send(BackgroundMessage::LogTyped(
+            format!("#️⃣ Updated hash in message of {}", line),
+            LogLevel::Debug,
+        ))
+        .ok();
+    }
+    for (notes_ref, copied) in &preview_data.copied_notes {
+        tx.send(BackgroundMessage::LogTyped(
+            format!("📝 Migrated {} note(s) in refs/notes/{}", copied, notes_ref),
+            LogLevel::Info,
+        ))
+        .ok();
     }
+    if let Some(backup) = &preview_data.backup {
+        tx.send(BackgroundMessage::LogTyped(
+            format!("💾 Old refs saved to undo history as {}", backup),
+            LogLevel::Debug,
+        ))
+        .ok();
+    }
+    if !preview_data.lost_signatures.is_empty() {
+        tx.send(BackgroundMessage::LogTyped(
+            format!(
+                "⚠️ {} signed commit(s) lost their signature",
+                preview_data.lost_signatures.len()
+            ),
+            LogLevel::Warning,
+        ))
+        .ok();
+    }
+    tx.send(BackgroundMessage::LogTyped(
+        format!(
+            "✅ {} commit(s) successfully rewritten ({} commits recreated)! Showing preview...",
+            preview_data.changes.len(),
+            preview_data.commit_map.len()
+        ),
+        LogLevel::Success,
+    ))
+    .ok();
+    tx.send(BackgroundMessage::Progress(1.0)).ok();
+    tx.send(BackgroundMessage::PreviewReady(Box::new(preview_data)))
+        .ok();
+    tx.send(BackgroundMessage::Done).ok();
+}
 
-    send_log("Collecting preview data...");
+pub fn scan_blobs_task(path: PathBuf, limit: usize, tx: Sender<BackgroundMessage>) {
+    tx.send(BackgroundMessage::Log(
+        "🔎 Scanning history for large blobs...".to_string(),
+    ))
+    .ok();
 
-    let repo = match git2::Repository::open(&path) {
-        Ok(r) => r,
-        Err(e) => {
-            tx.send(BackgroundMessage::Error(format!(
-                "Failed to open repository: {}",
-                e
+    match scan_blobs(&path, limit) {
+        Ok(blobs) => {
+            let total: u64 = blobs.iter().map(|blob| blob.size).sum();
+            tx.send(BackgroundMessage::Log(format!(
+                "✅ Listed the {} largest blob(s), {} in total",
+                blobs.len(),
+                format_size(total)
             )))
             .ok();
-            return;
+            tx.send(BackgroundMessage::BlobsScanned(blobs)).ok();
         }
-    };
-
-    let target_oid = match git2::Oid::from_str(&commit_hash) {
-        Ok(oid) => oid,
         Err(e) => {
             tx.send(BackgroundMessage::Error(format!(
-                "Invalid hash format: {}",
+                "Blob scan failed: {:#}",
                 e
             )))
             .ok();
-            return;
-        }
-    };
-
-    let target_commit = match repo.find_commit(target_oid) {
-        Ok(c) => c,
-        Err(e) => {
-            tx.send(BackgroundMessage::Error(format!("Commit not found: {}", e)))
-                .ok();
-            return;
         }
-    };
+    }
 
-    let old_message = target_commit
-        .message()
-        .unwrap_or("(no message)")
-        .lines()
-        .next()
-        .unwrap_or("(no message)")
-        .to_string();
+    tx.send(BackgroundMessage::Done).ok();
+}
 
-    let affected_commits = match crate::git::commands::get_original_refs(&path) {
-        Ok(refs) => refs,
-        Err(_) => Vec::new(),
-    };
+pub fn scan_secrets_task(path: PathBuf, custom_rules: Vec<String>, tx: Sender<BackgroundMessage>) {
+    tx.send(BackgroundMessage::Log(
+        "🕵 Scanning history for secrets...".to_string(),
+    ))
+    .ok();
+    tx.send(BackgroundMessage::Progress(0.0)).ok();
 
-    let diff_output = match crate::git::commands::get_git_log(&path, 10) {
-        Ok(log) => log,
-        Err(_) => "Failed to get diff".to_string(),
+    let mut report = |fraction: f32| {
+        tx.send(BackgroundMessage::Progress(fraction)).ok();
     };
+    match find_secrets(&path, &custom_rules, &mut report) {
+        Ok(findings) => {
+            let level = if findings.is_empty() {
+                LogLevel::Success
+            } else {
+                LogLevel::Warning
+            };
+            tx.send(BackgroundMessage::LogTyped(
+                format!("🕵 Secret scan found {} possible secret(s)", findings.len()),
+                level,
+            ))
+            .ok();
+            tx.send(BackgroundMessage::SecretsFound(findings)).ok();
+        }
+        Err(e) => {
+            tx.send(BackgroundMessage::Error(format!(
+                "Secret scan failed: {:#}",
+                e
+            )))
+            .ok();
+        }
+    }
 
-    let preview_data = crate::models::PreviewData::new(
-        commit_hash.clone(),
-        old_message,
-        new_message,
-        affected_commits,
-        diff_output,
-    );
-
-    send_log(&format!(
-        "✅ Commit {} successfully rewritten! Showing preview...",
-        &commit_hash[..8]
-    ));
     tx.send(BackgroundMessage::Progress(1.0)).ok();
-    tx.send(BackgroundMessage::PreviewReady(preview_data)).ok();
     tx.send(BackgroundMessage::Done).ok();
 }
 
@@ -259,6 +303,7 @@ pub fn rollback_changes_task(path: PathBuf, tx: Sender<BackgroundMessage>) {
                 "✅ Rollback completed! Restored {} refs",
                 restored_count
             ));
+            tx.send(BackgroundMessage::CommitRewritten).ok();
         }
         Err(e) => {
             tx.send(BackgroundMessage::Error(format!("Rollback error: {}", e)))
@@ -269,3 +314,70 @@ pub fn rollback_changes_task(path: PathBuf, tx: Sender<BackgroundMessage>) {
     tx.send(BackgroundMessage::Progress(1.0)).ok();
     tx.send(BackgroundMessage::Done).ok();
 }
+
+pub fn restore_backup_task(path: PathBuf, id: String, tx: Sender<BackgroundMessage>) {
+    tx.send(BackgroundMessage::Log(format!(
+        "Restoring refs from backup {}...",
+        id
+    )))
+    .ok();
+    tx.send(BackgroundMessage::Progress(0.3)).ok();
+
+    match restore_backup(&path, &id) {
+        Ok(restored) => {
+            tx.send(BackgroundMessage::Progress(0.7)).ok();
+            let mut message = format!("✅ Restored {} ref(s) from backup {}", restored.refs, id);
+            if let Some(snapshot) = restored.snapshot {
+                message.push_str(&format!("; their previous positions are in {}", snapshot));
+            }
+            tx.send(BackgroundMessage::LogTyped(message, LogLevel::Success))
+                .ok();
+            tx.send(BackgroundMessage::CommitRewritten).ok();
+        }
+        Err(e) => {
+            tx.send(BackgroundMessage::Error(format!("Restore error: {:#}", e)))
+                .ok();
+        }
+    }
+
+    tx.send(BackgroundMessage::Progress(1.0)).ok();
+    tx.send(BackgroundMessage::Done).ok();
+}
+
+pub fn push_branches_task(path: PathBuf, targets: Vec<PushTarget>, tx: Sender<BackgroundMessage>) {
+    tx.send(BackgroundMessage::Log(format!(
+        "Force-pushing {} branch(es) with lease...",
+        targets.len()
+    )))
+    .ok();
+    tx.send(BackgroundMessage::Progress(0.3)).ok();
+
+    match push_branches(&path, &targets) {
+        Ok(results) => {
+            for result in &results {
+                let line = format!(
+                    "{} {} → {}: {}",
+                    if result.accepted { "✅" } else { "⛔" },
+                    result.target.branch.trim_start_matches("refs/heads/"),
+                    result.target.remote_name(),
+                    result.message
+                );
+                let level = if result.accepted {
+                    LogLevel::Success
+                } else {
+                    LogLevel::Error
+                };
+                tx.send(BackgroundMessage::LogTyped(line, level)).ok();
+            }
+            tx.send(BackgroundMessage::Pushed(results)).ok();
+            tx.send(BackgroundMessage::CommitRewritten).ok();
+        }
+        Err(e) => {
+            tx.send(BackgroundMessage::Error(format!("Push error: {:#}", e)))
+                .ok();
+        }
+    }
+
+    tx.send(BackgroundMessage::Progress(1.0)).ok();
+    tx.send(BackgroundMessage::Done).ok();
+}
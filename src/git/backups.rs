@@ -0,0 +1,282 @@
+use crate::git::commit_map;
+use crate::git::rewrite::sync_worktree;
+use crate::models::{BackupInfo, BackupRef};
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use git2::{Oid, Repository};
+use std::collections::BTreeMap;
+
+/// Refs the app keeps for itself; none of them are live history.
+pub const APP_REF_ROOT: &str = "refs/commit-rewriter/";
+
+/// Every rewrite keeps its pre-images in a namespace of its own below this,
+/// so older operations can still be undone after newer ones.
+pub const BACKUP_ROOT: &str = "refs/commit-rewriter/backup/";
+
+const ID_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// The result of restoring a backup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Restored {
+    /// Refs moved back.
+    pub refs: usize,
+    /// The backup holding where those refs were before the restore.
+    pub snapshot: Option<String>,
+}
+
+/// Saves `refs` (name and target) under a new timestamped backup and
+/// returns its id.
+pub fn save_backup(repo: &Repository, refs: &[(String, Oid)]) -> Result<String> {
+    let stamp = Utc::now().format(ID_FORMAT).to_string();
+    let mut id = stamp.clone();
+    let mut n = 1;
+    while repo
+        .references_glob(&format!("{}{}/*", BACKUP_ROOT, id))?
+        .next()
+        .is_some()
+    {
+        n += 1;
+        id = format!("{}-{}", stamp, n);
+    }
+
+    for (name, oid) in refs {
+        repo.reference(
+            &format!("{}{}/{}", BACKUP_ROOT, id, name),
+            *oid,
+            false,
+            "commit-rewriter: backup",
+        )
+        .with_context(|| format!("Failed to back up {}", name))?;
+    }
+    Ok(id)
+}
+
+/// Every backup, newest first, with what each ref pointed at.
+pub fn list_backups(repo: &Repository) -> Result<Vec<BackupInfo>> {
+    let mut backups: BTreeMap<String, Vec<BackupRef>> = BTreeMap::new();
+    for (id, name, oid) in backup_refs(repo, None)? {
+        let (target, summary) = match repo.find_object(oid, None)?.peel_to_commit() {
+            Ok(commit) => (
+                short(commit.id()),
+                commit.summary().unwrap_or("").to_string(),
+            ),
+            Err(_) => (short(oid), String::new()),
+        };
+        let current = match current_target(repo, &name) {
+            Some(current) if current == oid => None,
+            Some(current) => Some(short(current)),
+            None => Some("gone".to_string()),
+        };
+        backups.entry(id).or_default().push(BackupRef {
+            name,
+            target,
+            summary,
+            current,
+        });
+    }
+
+    let mut backups: Vec<(String, Vec<BackupRef>)> = backups.into_iter().collect();
+    backups.sort_by(|(a, _), (b, _)| order_key(b).cmp(&order_key(a)));
+    Ok(backups
+        .into_iter()
+        .map(|(id, refs)| BackupInfo {
+            created: created_at(&id),
+            commit_map: commit_map::exists(repo, &id),
+            id,
+            refs,
+        })
+        .collect())
+}
+
+/// Moves every ref in backup `id` back to where it was. The current
+/// positions of the refs that move are saved as a new backup first, so a
+/// restore can itself be undone.
+pub fn restore_backup(repo: &Repository, id: &str) -> Result<Restored> {
+    let refs = backup_refs(repo, Some(id))?;
+    if refs.is_empty() {
+        anyhow::bail!("Backup {} not found", id);
+    }
+
+    let moving: Vec<(String, Oid)> = refs
+        .into_iter()
+        .filter(|(_, name, oid)| current_target(repo, name) != Some(*oid))
+        .map(|(_, name, oid)| (name, oid))
+        .collect();
+    let current: Vec<(String, Oid)> = moving
+        .iter()
+        .filter_map(|(name, _)| Some((name.clone(), current_target(repo, name)?)))
+        .collect();
+    let snapshot = if current.is_empty() {
+        None
+    } else {
+        Some(save_backup(repo, &current)?)
+    };
+
+    sync_worktree(repo, &moving)?;
+    for (name, oid) in &moving {
+        if name == "HEAD" {
+            repo.set_head_detached(*oid)?;
+        } else {
+            repo.reference(name, *oid, true, "commit-rewriter: restore backup")
+                .with_context(|| format!("Failed to restore {}", name))?;
+        }
+    }
+
+    Ok(Restored {
+        refs: moving.len(),
+        snapshot,
+    })
+}
+
+/// Deletes backup `id` and its commit map, returning how many refs it held.
+pub fn delete_backup(repo: &Repository, id: &str) -> Result<usize> {
+    let mut deleted = 0;
+    for reference in repo.references_glob(&format!("{}{}/*", BACKUP_ROOT, id))? {
+        reference?.delete()?;
+        deleted += 1;
+    }
+    commit_map::delete(repo, id)?;
+    Ok(deleted)
+}
+
+/// The refs saved in backup `id`, by name and target.
+pub fn backup_targets(repo: &Repository, id: &str) -> Result<Vec<(String, Oid)>> {
+    Ok(backup_refs(repo, Some(id))?
+        .into_iter()
+        .map(|(_, name, oid)| (name, oid))
+        .collect())
+}
+
+/// (backup id, ref name, target) for every ref in one or all backups.
+fn backup_refs(repo: &Repository, id: Option<&str>) -> Result<Vec<(String, String, Oid)>> {
+    let glob = format!(
+        "{}{}*",
+        BACKUP_ROOT,
+        id.map(|id| format!("{}/", id)).unwrap_or_default()
+    );
+    let mut refs = Vec::new();
+    for reference in repo.references_glob(&glob)? {
+        let reference = reference?;
+        let (Some(full), Some(oid)) = (reference.name(), reference.target()) else {
+            continue;
+        };
+        let Some((id, name)) = full.trim_start_matches(BACKUP_ROOT).split_once('/') else {
+            continue;
+        };
+        refs.push((id.to_string(), name.to_string(), oid));
+    }
+    Ok(refs)
+}
+
+fn current_target(repo: &Repository, name: &str) -> Option<Oid> {
+    if name == "HEAD" {
+        return repo.head().ok()?.target();
+    }
+    repo.find_reference(name).ok()?.target()
+}
+
+fn parse_stamp(id: &str) -> Option<NaiveDateTime> {
+    id.get(..15)
+        .and_then(|stamp| NaiveDateTime::parse_from_str(stamp, ID_FORMAT).ok())
+}
+
+/// Sorts ids by when they were taken, then by the `-N` suffix added for
+/// backups taken within the same second, which a plain string sort would
+/// put `-10` before `-2`.
+fn order_key(id: &str) -> (Option<NaiveDateTime>, u32, &str) {
+    let n = id
+        .get(15..)
+        .and_then(|suffix| suffix.strip_prefix('-'))
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1);
+    (parse_stamp(id), n, id)
+}
+
+fn created_at(id: &str) -> String {
+    parse_stamp(id)
+        .map(|time| {
+            Local
+                .from_utc_datetime(&time)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+fn short(oid: Oid) -> String {
+    oid.to_string()[..8].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+
+    #[test]
+    fn test_backups_restore_any_operation() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        let second = repo.commit("second", &[("a.txt", "2")]);
+        let third = repo.commit("third", &[("a.txt", "3")]);
+        let main = "refs/heads/main".to_string();
+
+        let older = save_backup(&repo.repo, &[(main.clone(), first)]).unwrap();
+        let newer = save_backup(&repo.repo, &[(main.clone(), second)]).unwrap();
+        assert_ne!(older, newer);
+
+        let backups = list_backups(&repo.repo).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].id, newer);
+        assert_eq!(backups[1].refs[0].name, main);
+        assert_eq!(backups[1].refs[0].summary, "first");
+        assert_eq!(backups[1].changed(), 1);
+
+        let restored = restore_backup(&repo.repo, &older).unwrap();
+        assert_eq!(restored.refs, 1);
+        assert_eq!(repo.repo.refname_to_id(&main).unwrap(), first);
+
+        // the restore saved where main was, so it can be undone too
+        let snapshot = restored.snapshot.unwrap();
+        restore_backup(&repo.repo, &snapshot).unwrap();
+        assert_eq!(repo.repo.refname_to_id(&main).unwrap(), third);
+
+        assert_eq!(delete_backup(&repo.repo, &older).unwrap(), 1);
+        assert!(restore_backup(&repo.repo, &older).is_err());
+    }
+
+    #[test]
+    fn test_backups_in_one_second_sort_by_suffix() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        for id in [
+            "20240101-000000",
+            "20240101-000000-2",
+            "20240101-000000-10",
+            "20240101-000001",
+        ] {
+            repo.repo
+                .reference(
+                    &format!("{}{}/refs/heads/main", BACKUP_ROOT, id),
+                    first,
+                    false,
+                    "test",
+                )
+                .unwrap();
+        }
+
+        let ids: Vec<String> = list_backups(&repo.repo)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.id)
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "20240101-000001",
+                "20240101-000000-10",
+                "20240101-000000-2",
+                "20240101-000000",
+            ]
+        );
+    }
+}
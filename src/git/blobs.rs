@@ -0,0 +1,147 @@
+use crate::git::rewrite::is_backup_ref;
+use crate::models::BlobInfo;
+use anyhow::Result;
+use git2::{ObjectType, Oid, Repository, Sort, Tree};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+/// Every commit reachable from HEAD or any ref (backups excluded), oldest
+/// first.
+pub fn reachable_commits(repo: &Repository) -> Result<Vec<Oid>> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    if repo.head().is_ok() {
+        revwalk.push_head()?;
+    }
+    for reference in repo.references()? {
+        let reference = reference?;
+        if reference.name().is_some_and(is_backup_ref) {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            revwalk.push(commit.id())?;
+        }
+    }
+    Ok(revwalk.collect::<std::result::Result<_, _>>()?)
+}
+
+/// Visits each blob once across many trees, skipping subtrees that were
+/// already walked.
+#[derive(Default)]
+pub struct BlobWalker {
+    seen_trees: HashSet<Oid>,
+    seen_blobs: HashSet<Oid>,
+}
+
+impl BlobWalker {
+    /// Calls `visit` with every blob in `tree` not seen before, and the path
+    /// it was found at.
+    pub fn walk(
+        &mut self,
+        repo: &Repository,
+        tree: &Tree,
+        visit: &mut dyn FnMut(Oid, &str) -> Result<()>,
+    ) -> Result<()> {
+        self.walk_at(repo, tree, "", visit)
+    }
+
+    fn walk_at(
+        &mut self,
+        repo: &Repository,
+        tree: &Tree,
+        prefix: &str,
+        visit: &mut dyn FnMut(Oid, &str) -> Result<()>,
+    ) -> Result<()> {
+        // everything below a known tree has already been visited
+        if !self.seen_trees.insert(tree.id()) {
+            return Ok(());
+        }
+
+        for entry in tree.iter() {
+            let path = format!("{}{}", prefix, String::from_utf8_lossy(entry.name_bytes()));
+            match entry.kind() {
+                Some(ObjectType::Tree) => {
+                    let subtree = repo.find_tree(entry.id())?;
+                    self.walk_at(repo, &subtree, &format!("{}/", path), visit)?;
+                }
+                Some(ObjectType::Blob) if self.seen_blobs.insert(entry.id()) => {
+                    visit(entry.id(), &path)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Size, path, blob and commit, ordered so the smallest blob (then the
+/// latest path) is the first to be dropped from the top `limit`.
+type Candidate = (u64, Reverse<String>, Oid, Oid);
+
+/// The `limit` largest blobs reachable from any ref, largest first, each
+/// with the first path and oldest commit it appears in. Only the current
+/// top `limit` are kept while walking, so memory stays bounded on large
+/// histories.
+pub fn largest_blobs(repo: &Repository, limit: usize) -> Result<Vec<BlobInfo>> {
+    let odb = repo.odb()?;
+    let mut walker = BlobWalker::default();
+    let mut largest: BinaryHeap<Reverse<Candidate>> = BinaryHeap::with_capacity(limit + 1);
+
+    for oid in reachable_commits(repo)? {
+        let commit = repo.find_commit(oid)?;
+        // the walker reports each blob id once, at its first sighting
+        walker.walk(repo, &commit.tree()?, &mut |blob, path| {
+            let (size, _) = odb.read_header(blob)?;
+            let size = size as u64;
+            if limit == 0
+                || largest
+                    .peek()
+                    .is_some_and(|Reverse(smallest)| largest.len() >= limit && size < smallest.0)
+            {
+                return Ok(());
+            }
+            largest.push(Reverse((size, Reverse(path.to_string()), blob, oid)));
+            if largest.len() > limit {
+                largest.pop();
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(largest
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((size, Reverse(path), blob, commit))| BlobInfo {
+            oid: blob.to_string(),
+            path,
+            size,
+            commit_hash: commit.to_string(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+
+    #[test]
+    fn test_largest_blobs_reports_first_path_and_commit() {
+        let repo = TestRepo::new();
+        let first = repo.commit(
+            "assets",
+            &[("assets/video.bin", &"x".repeat(4096)), ("a.txt", "1")],
+        );
+        repo.commit("more", &[("a.txt", "22"), ("copy.bin", &"x".repeat(4096))]);
+        let third = repo.commit("logo", &[("logo.png", &"y".repeat(1024))]);
+
+        let blobs = largest_blobs(&repo.repo, 2).unwrap();
+
+        assert_eq!(blobs.len(), 2);
+        assert_eq!(blobs[0].path, "assets/video.bin");
+        assert_eq!(blobs[0].size, 4096);
+        assert_eq!(blobs[0].commit_hash, first.to_string());
+        assert_eq!(blobs[1].path, "logo.png");
+        assert_eq!(blobs[1].commit_hash, third.to_string());
+    }
+}
@@ -1,48 +1,13 @@
-use anyhow::Result;
+use crate::git::rewrite::sync_worktree;
+use anyhow::{Context, Result};
+use git2::{Oid, Repository};
 use std::path::Path;
-use std::process::{Command, Stdio};
-
-pub fn run_git_filter_branch<P: AsRef<Path>>(
-    repo_path: P,
-    commit_hash: &str,
-    new_message: &str,
-    modify_all_branches: bool,
-    branch_name: Option<&str>,
-) -> Result<std::process::Child> {
-    let mut args = vec![
-        "filter-branch".to_string(),
-        "-f".to_string(),
-        "--msg-filter".to_string(),
-        format!(
-            r#"if [ "$GIT_COMMIT" = "{}" ]; then echo '{}'; else cat; fi"#,
-            commit_hash,
-            new_message.replace('\'', "'\\''")
-        ),
-        "--".to_string(),
-    ];
-
-    if modify_all_branches {
-        args.push("--all".to_string());
-    } else if let Some(branch) = branch_name {
-        args.push(branch.to_string());
-    } else {
-        args.push("--all".to_string());
-    }
-
-    let child = Command::new("git")
-        .current_dir(repo_path)
-        .args(&args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
-
-    Ok(child)
-}
+use std::process::Command;
 
 pub fn get_original_refs<P: AsRef<Path>>(repo_path: P) -> Result<Vec<String>> {
     let output = Command::new("git")
         .current_dir(repo_path)
-        .args(&["for-each-ref", "--format=%(refname)", "refs/original/"])
+        .args(["for-each-ref", "--format=%(refname)", "refs/original/"])
         .output()?;
 
     let output_str = String::from_utf8_lossy(&output.stdout);
@@ -57,37 +22,32 @@ pub fn get_original_refs<P: AsRef<Path>>(repo_path: P) -> Result<Vec<String>> {
 
 pub fn restore_original_refs<P: AsRef<Path>>(repo_path: P) -> Result<usize> {
     let refs = get_original_refs(repo_path.as_ref())?;
-    let mut restored_count = 0;
-
-    for original_ref in refs {
-        let target_ref = original_ref.replace("refs/original/", "");
-
-        let hash_output = Command::new("git")
-            .current_dir(repo_path.as_ref())
-            .args(&["rev-parse", &original_ref])
-            .output()?;
-
-        let hash = String::from_utf8_lossy(&hash_output.stdout)
-            .trim()
-            .to_string();
-
-        let result = Command::new("git")
-            .current_dir(repo_path.as_ref())
-            .args(&["update-ref", "-f", &target_ref, &hash])
-            .output();
-
-        if result.is_ok() {
-            restored_count += 1;
+    let repo = Repository::open(repo_path.as_ref())?;
+    let moves: Vec<(String, Oid)> = refs
+        .iter()
+        .filter_map(|original_ref| {
+            let target_ref = original_ref.replace("refs/original/", "");
+            Some((target_ref, repo.refname_to_id(original_ref).ok()?))
+        })
+        .collect();
+    sync_worktree(&repo, &moves)?;
+
+    for (target_ref, oid) in &moves {
+        if target_ref == "HEAD" {
+            repo.set_head_detached(*oid)?;
+        } else {
+            repo.reference(target_ref, *oid, true, "commit-rewriter: rollback")
+                .with_context(|| format!("Failed to restore {}", target_ref))?;
         }
     }
 
-    Ok(restored_count)
+    Ok(moves.len())
 }
 
 pub fn get_git_log<P: AsRef<Path>>(repo_path: P, lines: usize) -> Result<String> {
     let output = Command::new("git")
         .current_dir(repo_path)
-        .args(&[
+        .args([
             "log",
             "--oneline",
             "--graph",
@@ -102,12 +62,27 @@ pub fn get_git_log<P: AsRef<Path>>(repo_path: P, lines: usize) -> Result<String>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::test_utils::TestRepo;
 
     #[test]
     fn test_get_git_log() {
-        let current_dir = std::env::current_dir().unwrap();
-        if let Ok(log) = get_git_log(current_dir, 5) {
-            assert!(!log.is_empty() || true);
-        }
+        let repo = TestRepo::new();
+        repo.commit("Initial commit", &[("a.txt", "1")]);
+
+        let log = get_git_log(repo.path(), 5).unwrap();
+        assert!(log.contains("Initial commit"));
+    }
+
+    #[test]
+    fn test_restore_original_refs() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        repo.commit("second", &[("a.txt", "2")]);
+        repo.repo
+            .reference("refs/original/refs/heads/main", first, false, "test")
+            .unwrap();
+
+        assert_eq!(restore_original_refs(repo.path()).unwrap(), 1);
+        assert_eq!(repo.repo.refname_to_id("refs/heads/main").unwrap(), first);
     }
 }
@@ -0,0 +1,150 @@
+use crate::git::journal::STATE_DIR;
+use anyhow::{Context, Result};
+use git2::{Oid, Repository};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Directory inside the app's state dir holding one map per rewrite, named
+/// after the rewrite's undo-history backup.
+const MAP_DIR: &str = "commit-maps";
+
+/// Export formats for a commit map.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MapFormat {
+    /// Tab-separated `old new` lines under a header, like filter-repo's
+    /// `commit-map`.
+    Tsv,
+    /// An object from old hash to new hash.
+    Json,
+}
+
+impl MapFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            MapFormat::Tsv => "tsv",
+            MapFormat::Json => "json",
+        }
+    }
+}
+
+fn map_path(repo: &Repository, id: &str) -> PathBuf {
+    repo.path()
+        .join(STATE_DIR)
+        .join(MAP_DIR)
+        .join(format!("{}.tsv", id))
+}
+
+/// Records which new commit replaced each old one for the rewrite saved as
+/// backup `id`. Commits the rewrite removed map to the zero id, as in
+/// filter-repo.
+pub fn save(
+    repo: &Repository,
+    id: &str,
+    commit_map: &HashMap<Oid, Oid>,
+    removed: &[Oid],
+) -> Result<()> {
+    let mut entries: Vec<(String, String)> = commit_map
+        .iter()
+        .map(|(old, new)| (old.to_string(), new.to_string()))
+        .chain(
+            removed
+                .iter()
+                .map(|old| (old.to_string(), Oid::zero().to_string())),
+        )
+        .collect();
+    entries.sort();
+
+    let path = map_path(repo, id);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, to_tsv(&entries))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The map recorded for backup `id`, if that rewrite left one.
+pub fn load(repo: &Repository, id: &str) -> Result<Option<Vec<(String, String)>>> {
+    let path = map_path(repo, id);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(Some(
+        text.lines()
+            .skip(1)
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+            .collect(),
+    ))
+}
+
+pub fn exists(repo: &Repository, id: &str) -> bool {
+    map_path(repo, id).exists()
+}
+
+pub fn delete(repo: &Repository, id: &str) -> Result<()> {
+    let path = map_path(repo, id);
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn to_tsv(entries: &[(String, String)]) -> String {
+    let mut text = String::from("old\tnew\n");
+    for (old, new) in entries {
+        text.push_str(&format!("{}\t{}\n", old, new));
+    }
+    text
+}
+
+fn to_json(entries: &[(String, String)]) -> Result<String> {
+    let map: BTreeMap<&str, &str> = entries
+        .iter()
+        .map(|(old, new)| (old.as_str(), new.as_str()))
+        .collect();
+    Ok(serde_json::to_string_pretty(&map)?)
+}
+
+pub fn export(entries: &[(String, String)], format: MapFormat) -> Result<String> {
+    match format {
+        MapFormat::Tsv => Ok(to_tsv(entries)),
+        MapFormat::Json => to_json(entries),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+
+    #[test]
+    fn test_commit_map_round_trip() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        let second = repo.commit("second", &[("a.txt", "2")]);
+        let third = repo.commit("third", &[("a.txt", "3")]);
+
+        assert_eq!(load(&repo.repo, "20240101-000000").unwrap(), None);
+        let map = HashMap::from([(second, third)]);
+        save(&repo.repo, "20240101-000000", &map, &[first]).unwrap();
+        assert!(exists(&repo.repo, "20240101-000000"));
+
+        let entries = load(&repo.repo, "20240101-000000").unwrap().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.contains(&(second.to_string(), third.to_string())));
+        assert!(entries.contains(&(first.to_string(), "0".repeat(40))));
+
+        let tsv = export(&entries, MapFormat::Tsv).unwrap();
+        assert!(tsv.starts_with("old\tnew\n"));
+        assert_eq!(tsv.lines().count(), 3);
+        let json: BTreeMap<String, String> =
+            serde_json::from_str(&export(&entries, MapFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[&second.to_string()], third.to_string());
+
+        delete(&repo.repo, "20240101-000000").unwrap();
+        assert!(!exists(&repo.repo, "20240101-000000"));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::git::backups;
+use crate::git::rewrite::sync_worktree;
+use crate::models::{Journal, JournalPhase, JournalRef};
+use anyhow::{Context, Result};
+use git2::{Oid, Repository};
+use std::path::PathBuf;
+
+/// Directory inside `.git` holding the app's own state.
+pub const STATE_DIR: &str = "commit-rewriter";
+const JOURNAL_FILE: &str = "journal.json";
+
+fn journal_path(repo: &Repository) -> PathBuf {
+    repo.path().join(STATE_DIR).join(JOURNAL_FILE)
+}
+
+/// The unfinished operation left behind by an earlier run, if any.
+pub fn load(repo: &Repository) -> Result<Option<Journal>> {
+    let path = journal_path(repo);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let journal = serde_json::from_str(&text)
+        .with_context(|| format!("Corrupt operation journal {}", path.display()))?;
+    Ok(Some(journal))
+}
+
+/// Writes the journal through a temporary file so a crash never leaves a
+/// half-written one.
+fn save(repo: &Repository, journal: &Journal) -> Result<()> {
+    let path = journal_path(repo);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(journal)?)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Opens a journal for a new operation. Refuses while an earlier one is
+/// still unresolved, since its record would be lost.
+pub fn begin(repo: &Repository, operation: &str) -> Result<()> {
+    if let Some(pending) = load(repo)? {
+        anyhow::bail!(
+            "An earlier operation ({}) is unfinished; complete or roll it back first",
+            pending.operation
+        );
+    }
+    save(
+        repo,
+        &Journal {
+            operation: operation.to_string(),
+            started: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            phase: JournalPhase::Rewriting,
+            refs: Vec::new(),
+            backup: None,
+        },
+    )
+}
+
+/// Records the refs about to move and the backup holding their old
+/// positions. Does nothing when no operation is open, so the rewrite engine
+/// can run without a journal.
+pub fn record_ref_updates(
+    repo: &Repository,
+    updates: &[(String, Oid, Oid)],
+    backup: Option<&str>,
+) -> Result<()> {
+    let Some(mut journal) = load(repo)? else {
+        return Ok(());
+    };
+    journal.phase = JournalPhase::UpdatingRefs;
+    journal.backup = backup.map(str::to_string);
+    journal.refs = updates
+        .iter()
+        .map(|(name, old, new)| JournalRef {
+            name: name.clone(),
+            old: old.to_string(),
+            new: new.to_string(),
+        })
+        .collect();
+    save(repo, &journal)
+}
+
+/// Moves the open operation, if any, to `phase`.
+pub fn set_phase(repo: &Repository, phase: JournalPhase) -> Result<()> {
+    let Some(mut journal) = load(repo)? else {
+        return Ok(());
+    };
+    journal.phase = phase;
+    save(repo, &journal)
+}
+
+/// Closes the open operation.
+pub fn finish(repo: &Repository) -> Result<()> {
+    let path = journal_path(repo);
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Resolves an unfinished operation by moving its refs to their new values
+/// (`complete`) or back to their old ones, then closes it. A rolled-back
+/// operation never happened, so its undo-history backup and commit map go
+/// too. Returns how many refs moved.
+pub fn recover(repo: &Repository, complete: bool) -> Result<usize> {
+    let Some(journal) = load(repo)? else {
+        return Ok(0);
+    };
+
+    let moves = journal
+        .refs
+        .iter()
+        .map(|entry| {
+            let target = if complete { &entry.new } else { &entry.old };
+            Ok((entry.name.clone(), Oid::from_str(target)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    sync_worktree(repo, &moves)?;
+
+    let mut moved = 0;
+    for (name, target) in moves {
+        if name == "HEAD" {
+            if repo.head().ok().and_then(|head| head.target()) != Some(target) {
+                repo.set_head_detached(target)?;
+                moved += 1;
+            }
+        } else if repo.refname_to_id(&name).ok() != Some(target) {
+            repo.reference(&name, target, true, "commit-rewriter: recover")
+                .with_context(|| format!("Failed to move {}", name))?;
+            moved += 1;
+        }
+    }
+
+    if let (false, Some(id)) = (complete, &journal.backup) {
+        backups::delete_backup(repo, id)?;
+    }
+    finish(repo)?;
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+
+    #[test]
+    fn test_recover_interrupted_ref_update() {
+        let repo = TestRepo::new();
+        let old = repo.commit("old", &[("a.txt", "1")]);
+        let new = repo.commit("new", &[("a.txt", "2")]);
+        let main = "refs/heads/main".to_string();
+
+        // nothing is journaled outside an operation
+        record_ref_updates(&repo.repo, &[(main.clone(), old, new)], None).unwrap();
+        assert_eq!(load(&repo.repo).unwrap(), None);
+
+        begin(&repo.repo, "Rewrite 1 commit(s)").unwrap();
+        assert!(begin(&repo.repo, "another").is_err());
+        record_ref_updates(&repo.repo, &[(main.clone(), old, new)], None).unwrap();
+        let journal = load(&repo.repo).unwrap().unwrap();
+        assert_eq!(journal.phase, JournalPhase::UpdatingRefs);
+        assert_eq!(journal.refs[0].old, old.to_string());
+
+        // main already points at `new`, as if the crash came after moving it
+        assert_eq!(recover(&repo.repo, false).unwrap(), 1);
+        assert_eq!(repo.repo.refname_to_id(&main).unwrap(), old);
+        assert_eq!(load(&repo.repo).unwrap(), None);
+
+        begin(&repo.repo, "Rewrite 1 commit(s)").unwrap();
+        record_ref_updates(&repo.repo, &[(main.clone(), old, new)], None).unwrap();
+        assert_eq!(recover(&repo.repo, true).unwrap(), 1);
+        assert_eq!(repo.repo.refname_to_id(&main).unwrap(), new);
+    }
+}
@@ -1,6 +1,25 @@
+pub mod backups;
+pub mod blobs;
 pub mod commands;
+pub mod commit_map;
+pub mod journal;
+pub mod notes;
 pub mod operations;
+pub mod preflight;
+pub mod push;
+pub mod remotes;
 pub mod repository;
+pub mod rewrite;
+pub mod secrets;
+pub mod signing;
+pub mod tags;
+#[cfg(test)]
+pub mod test_utils;
+pub mod tree_filter;
 
-pub use operations::{get_current_branch, rollback_changes};
+pub use operations::{
+    begin_operation, delete_backup, discard_original_refs, export_commit_map, finish_operation,
+    get_current_branch, get_user_identity, list_backups, list_refs, pending_operation, preflight,
+    push_branches, push_targets, recover_operation, restore_backup, rollback_changes,
+};
 pub use repository::{GitRepository, GitRepositoryImpl};
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use git2::{Oid, Repository, Signature};
+use glob::Pattern;
+use std::collections::HashMap;
+
+pub const NOTES_NAMESPACE: &str = "refs/notes/";
+
+/// libgit2 only writes notes through a ref, so copies are made on a scratch
+/// ref here and the real notes ref moves along with the rest of the rewrite.
+const SCRATCH_ROOT: &str = "refs/commit-rewriter/scratch/";
+
+/// A notes ref that gained notes for rewritten commits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NotesUpdate {
+    pub name: String,
+    pub old_oid: Oid,
+    pub new_oid: Oid,
+    /// Notes copied onto rewritten commits.
+    pub copied: usize,
+}
+
+/// The notes refs matching any of `patterns`, given as full names, names
+/// relative to `refs/notes/` or globs. No patterns picks every notes ref.
+pub fn matching_notes_refs(repo: &Repository, patterns: &[String]) -> Result<Vec<String>> {
+    let patterns = patterns
+        .iter()
+        .map(|p| Pattern::new(p).with_context(|| format!("Invalid notes ref pattern: {}", p)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut names = Vec::new();
+    for reference in repo.references_glob(&format!("{}*", NOTES_NAMESPACE))? {
+        let Some(name) = reference?.name().map(str::to_string) else {
+            continue;
+        };
+        let short = name.trim_start_matches(NOTES_NAMESPACE);
+        if patterns.is_empty()
+            || patterns
+                .iter()
+                .any(|p| p.matches(&name) || p.matches(short))
+        {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+/// Copies the notes in each of `notes_refs` from every rewritten commit in
+/// `commit_map` to its replacement, like `notes.rewriteRef` does for rebase.
+/// When several commits were squashed into one their notes are
+/// concatenated, as with git's default `notes.rewriteMode`. The notes refs
+/// themselves are left alone; each update carries the new notes commit for
+/// the caller to move the ref to.
+pub fn copy_notes(
+    repo: &Repository,
+    notes_refs: &[String],
+    commit_map: &HashMap<Oid, Oid>,
+) -> Result<Vec<NotesUpdate>> {
+    // oldest first, so squashed notes are concatenated in history order
+    let mut rewritten: Vec<(i64, Oid, Oid)> = commit_map
+        .iter()
+        .filter(|(old, new)| old != new)
+        .map(|(old, new)| {
+            let time = repo.find_commit(*old)?.time().seconds();
+            Ok((time, *old, *new))
+        })
+        .collect::<Result<_>>()?;
+    rewritten.sort();
+
+    let mut updates = Vec::new();
+    for name in notes_refs {
+        let Some(old_oid) = repo
+            .find_reference(name)
+            .ok()
+            .and_then(|reference| reference.target())
+        else {
+            continue;
+        };
+
+        let scratch = format!("{}{}", SCRATCH_ROOT, name);
+        repo.reference(&scratch, old_oid, true, "commit-rewriter: copy notes")?;
+        let copied = copy_ref_notes(repo, &scratch, &rewritten);
+        let new_oid = repo.refname_to_id(&scratch);
+        repo.find_reference(&scratch)?.delete()?;
+        let copied = copied.with_context(|| format!("Failed to copy notes in {}", name))?;
+        if copied == 0 {
+            continue;
+        }
+
+        updates.push(NotesUpdate {
+            name: name.clone(),
+            old_oid,
+            new_oid: new_oid?,
+            copied,
+        });
+    }
+    Ok(updates)
+}
+
+fn copy_ref_notes(repo: &Repository, name: &str, rewritten: &[(i64, Oid, Oid)]) -> Result<usize> {
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("commit-rewriter", "commit-rewriter@localhost"))?;
+
+    let mut copied = 0;
+    for (_, old, new) in rewritten {
+        let Ok(note) = repo.find_note(Some(name), *old) else {
+            continue;
+        };
+        let message = String::from_utf8_lossy(note.message_bytes()).into_owned();
+
+        let text = match repo.find_note(Some(name), *new) {
+            Ok(existing) => {
+                let existing = String::from_utf8_lossy(existing.message_bytes()).into_owned();
+                if existing == message {
+                    continue;
+                }
+                format!("{}\n{}", existing.trim_end_matches('\n'), message)
+            }
+            Err(_) => message,
+        };
+        repo.note(&signature, &signature, Some(name), *new, &text, true)?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+
+    #[test]
+    fn test_copy_notes_follows_and_concatenates() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        let second = repo.commit("second", &[("a.txt", "2")]);
+        let squashed = repo.commit("squashed", &[("a.txt", "3")]);
+        let untouched = repo.commit("untouched", &[("a.txt", "4")]);
+
+        let sig = TestRepo::signature();
+        let ci = "refs/notes/ci".to_string();
+        repo.repo
+            .note(&sig, &sig, Some(&ci), first, "build: green\n", false)
+            .unwrap();
+        repo.repo
+            .note(&sig, &sig, Some(&ci), second, "build: red\n", false)
+            .unwrap();
+        repo.repo
+            .note(&sig, &sig, Some(&ci), untouched, "build: skipped\n", false)
+            .unwrap();
+        let before = repo.repo.refname_to_id(&ci).unwrap();
+
+        let commit_map = HashMap::from([(first, squashed), (second, squashed)]);
+        let refs = vec![ci.clone(), "refs/notes/missing".to_string()];
+        let updates = copy_notes(&repo.repo, &refs, &commit_map).unwrap();
+
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].copied, 2);
+        assert_eq!(updates[0].old_oid, before);
+        assert_eq!(repo.repo.refname_to_id(&ci).unwrap(), before);
+        assert!(repo
+            .repo
+            .references_glob(&format!("{}*", SCRATCH_ROOT))
+            .unwrap()
+            .next()
+            .is_none());
+        repo.repo
+            .reference(&ci, updates[0].new_oid, true, "test")
+            .unwrap();
+        let note = repo.repo.find_note(Some(&ci), squashed).unwrap();
+        let message = note.message().unwrap();
+        assert!(message.contains("build: green\n") && message.contains("build: red\n"));
+        // the old commits keep theirs
+        assert!(repo.repo.find_note(Some(&ci), first).is_ok());
+
+        let none = copy_notes(&repo.repo, &refs, &HashMap::new()).unwrap();
+        assert!(none.is_empty());
+
+        repo.repo
+            .note(
+                &sig,
+                &sig,
+                Some("refs/notes/review"),
+                first,
+                "lgtm\n",
+                false,
+            )
+            .unwrap();
+        let all = matching_notes_refs(&repo.repo, &[]).unwrap();
+        assert_eq!(all, vec!["refs/notes/ci", "refs/notes/review"]);
+        let picked = matching_notes_refs(&repo.repo, &["rev*".to_string()]).unwrap();
+        assert_eq!(picked, vec!["refs/notes/review"]);
+    }
+}
@@ -1,59 +1,353 @@
-use crate::git::commands::{
-    get_git_log, get_original_refs, restore_original_refs, run_git_filter_branch,
+use crate::git::backups::{self, Restored};
+use crate::git::blobs::largest_blobs;
+use crate::git::commands::{get_git_log, restore_original_refs};
+use crate::git::commit_map::{self, MapFormat};
+use crate::git::journal;
+use crate::git::notes::{matching_notes_refs, NOTES_NAMESPACE};
+use crate::git::preflight::check as preflight_check;
+use crate::git::push;
+use crate::git::repository::{identity_of, time_of, GitRepository, GitRepositoryImpl};
+use crate::git::rewrite::{CommitEdit, RewritePlan, BACKUP_NAMESPACE};
+use crate::git::secrets::{scan_secrets, secret_rules};
+use crate::git::signing::Signer;
+use crate::git::tree_filter::TreeFilter;
+use crate::models::{
+    BackupInfo, BlobInfo, CommitChange, Identity, Journal, PendingEdit, PreflightReport,
+    PreviewData, PushResult, PushTarget, RefEntry, RewriteOptions, SecretFinding,
 };
-use crate::git::repository::{GitRepository, GitRepositoryImpl};
-use crate::models::PreviewData;
 use anyhow::Result;
 use git2::Repository;
 use std::path::Path;
 
-pub fn rewrite_commit<P: AsRef<Path>>(
+pub fn rewrite_commits<P: AsRef<Path>>(
     repo_path: P,
-    commit_hash: &str,
-    new_message: &str,
+    edits: &[PendingEdit],
+    order: &[String],
     modify_all_branches: bool,
     branch_name: Option<&str>,
+    options: &RewriteOptions,
 ) -> Result<PreviewData> {
     let repo = Repository::open(repo_path.as_ref())?;
-    let target_oid = git2::Oid::from_str(commit_hash)?;
-    let target_commit = repo.find_commit(target_oid)?;
-
-    let old_message = target_commit
-        .message()
-        .unwrap_or("(no message)")
-        .lines()
-        .next()
-        .unwrap_or("(no message)")
-        .to_string();
-
-    let mut child = run_git_filter_branch(
-        repo_path.as_ref(),
-        commit_hash,
-        new_message,
-        modify_all_branches,
-        branch_name,
-    )?;
-
-    let status = child.wait()?;
-
-    if !status.success() {
-        anyhow::bail!("git filter-branch failed with status: {}", status);
+    let git_repo = GitRepositoryImpl::open(repo_path.as_ref())?;
+
+    let signer = if options.resign_commits {
+        Some(Signer::from_config(&repo)?)
+    } else {
+        None
+    };
+    let signed_with = signer.as_ref().map(|signer| signer.describe());
+    let tag_signer = match (&signer, options.resign_tags) {
+        (Some(signer), true) => Some(signer.clone()),
+        (None, true) => Some(Signer::from_config(&repo)?),
+        (_, false) => None,
+    };
+
+    let refs = match &options.ref_selection {
+        Some(selection) => {
+            let refs = selection.resolve(&git_repo.list_refs()?)?;
+            if refs.is_empty() {
+                anyhow::bail!("None of the picked refs exist any more");
+            }
+            refs
+        }
+        None => git_repo.rewrite_refs(modify_all_branches, branch_name)?,
+    };
+    let notes_refs = if options.copy_notes {
+        let patterns: Vec<String> = options
+            .notes_refs
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        matching_notes_refs(&repo, &patterns)?
+    } else {
+        Vec::new()
+    };
+
+    let mut plan = RewritePlan {
+        refs,
+        signer,
+        rewrite_tags: options.rewrite_tags,
+        tag_signer,
+        notes_refs,
+        update_hash_refs: options.update_hash_refs,
+        tree_filter: TreeFilter {
+            purge: options.purge_paths.clone(),
+            prune_empty: options.prune_empty,
+            strip_blobs: options
+                .strip_blobs
+                .iter()
+                .map(|hash| git2::Oid::from_str(hash))
+                .collect::<std::result::Result<_, _>>()?,
+            max_blob_size: options.max_blob_size,
+            replace_text: options.replace_text.clone(),
+            replacement: options.replacement.clone(),
+        },
+        ..Default::default()
+    };
+    let mut changes = Vec::new();
+
+    for edit in edits {
+        let target_oid = git2::Oid::from_str(&edit.commit_hash)?;
+        let target_commit = repo.find_commit(target_oid)?;
+
+        let old_message = String::from_utf8_lossy(target_commit.message_raw_bytes())
+            .trim_end()
+            .to_string();
+        let old_author = identity_of(&target_commit.author());
+        let old_committer = identity_of(&target_commit.committer());
+        let old_author_time = time_of(&target_commit.author());
+        let old_committer_time = time_of(&target_commit.committer());
+
+        changes.push(CommitChange {
+            commit_hash: edit.commit_hash.clone(),
+            new_message: edit
+                .new_message
+                .clone()
+                .unwrap_or_else(|| old_message.clone()),
+            old_message,
+            new_author: edit.author.apply(&old_author).to_string(),
+            old_author: old_author.to_string(),
+            new_committer: edit.committer.apply(&old_committer).to_string(),
+            old_committer: old_committer.to_string(),
+            old_author_date: old_author_time.format(),
+            new_author_date: edit.author.apply_time(old_author_time)?.format(),
+            old_committer_date: old_committer_time.format(),
+            new_committer_date: edit.committer.apply_time(old_committer_time)?.format(),
+            squashed: edit
+                .squash
+                .iter()
+                .map(|hash| hash.chars().take(8).collect())
+                .collect(),
+            dropped: edit.drop,
+        });
+
+        plan.edits.insert(
+            target_oid,
+            CommitEdit {
+                message: edit.new_message.clone(),
+                author: edit.author.clone(),
+                committer: edit.committer.clone(),
+                squash: edit
+                    .squash
+                    .iter()
+                    .map(|hash| git2::Oid::from_str(hash))
+                    .collect::<std::result::Result<_, _>>()?,
+                drop: edit.drop,
+            },
+        );
     }
 
-    let affected_commits = get_original_refs(repo_path.as_ref())?;
+    let mut reordered = Vec::new();
+    for hash in order {
+        let oid = git2::Oid::from_str(hash)?;
+        let commit = repo.find_commit(oid)?;
+        reordered.push(format!(
+            "{} {}",
+            &hash[..8.min(hash.len())],
+            commit.summary().unwrap_or("")
+        ));
+        plan.order.push(oid);
+    }
+
+    let outcome = git_repo.rewrite_history(&plan)?;
+
+    let mut pruned = Vec::new();
+    for oid in &outcome.pruned {
+        let commit = repo.find_commit(*oid)?;
+        pruned.push(format!(
+            "{} {}",
+            &oid.to_string()[..8],
+            commit.summary().unwrap_or("")
+        ));
+    }
+
+    let affected_commits = outcome
+        .ref_updates
+        .iter()
+        .map(|update| update.name.clone())
+        .collect();
+    let mut commit_map: Vec<(String, String)> = outcome
+        .commit_map
+        .iter()
+        .map(|(old, new)| (old.to_string(), new.to_string()))
+        .collect();
+    commit_map.sort();
     let diff_output = get_git_log(repo_path.as_ref(), 10)?;
 
-    Ok(PreviewData::new(
-        commit_hash.to_string(),
-        old_message,
-        new_message.to_string(),
-        affected_commits,
-        diff_output,
-    ))
+    let mut preview = PreviewData::new(changes, affected_commits, commit_map, diff_output);
+    preview.lost_signatures = outcome
+        .lost_signatures
+        .iter()
+        .map(|oid| oid.to_string().chars().take(8).collect())
+        .collect();
+    preview.signed_with = signed_with;
+    preview.reordered = reordered;
+    preview.purged_paths = plan.tree_filter.purge.clone();
+    preview.pruned = pruned;
+    preview.stripped_blobs = outcome.stripped_blobs;
+    preview.stripped_bytes = outcome.stripped_bytes;
+    preview.text_rules = plan.tree_filter.replace_text.len();
+    preview.replaced_blobs = outcome.replaced_blobs;
+    preview.backup = outcome.backup.clone();
+    preview.copied_notes = outcome
+        .notes
+        .iter()
+        .map(|update| {
+            (
+                update.name.trim_start_matches(NOTES_NAMESPACE).to_string(),
+                update.copied,
+            )
+        })
+        .collect();
+    preview.hash_refs = outcome
+        .hash_refs
+        .iter()
+        .map(|update| {
+            format!(
+                "{}: {} → {}",
+                &update.commit.to_string()[..8],
+                update.old,
+                update.new
+            )
+        })
+        .collect();
+    preview.moved_tags = outcome
+        .tags
+        .iter()
+        .map(|tag| {
+            let mut line = format!(
+                "{} {} → {}",
+                tag.name.trim_start_matches("refs/tags/"),
+                &tag.old_commit.to_string()[..8],
+                &tag.new_commit.to_string()[..8]
+            );
+            if tag.annotated {
+                line.push_str(match (tag.signed, tag.resigned) {
+                    (true, true) => " (annotated, re-signed)",
+                    (true, false) => " (annotated, signature dropped)",
+                    _ => " (annotated)",
+                });
+            }
+            line
+        })
+        .collect();
+
+    Ok(preview)
+}
+
+/// The largest blobs anywhere in the repository's history, largest first.
+pub fn scan_blobs<P: AsRef<Path>>(repo_path: P, limit: usize) -> Result<Vec<BlobInfo>> {
+    let repo = Repository::open(repo_path)?;
+    largest_blobs(&repo, limit)
 }
 
+/// Runs the built-in secret rules and `custom_rules` over all of history.
+pub fn find_secrets<P: AsRef<Path>>(
+    repo_path: P,
+    custom_rules: &[String],
+    progress: &mut dyn FnMut(f32),
+) -> Result<Vec<SecretFinding>> {
+    let repo = Repository::open(repo_path)?;
+    let rules = secret_rules(custom_rules)?;
+    scan_secrets(&repo, &rules, progress)
+}
+
+pub fn list_refs<P: AsRef<Path>>(repo_path: P) -> Result<Vec<RefEntry>> {
+    GitRepositoryImpl::open(repo_path)?.list_refs()
+}
+
+/// Undoes an unconfirmed rewrite through its journal, which records every
+/// ref it moved, notes refs included. Without an open journal, e.g. after an
+/// external filter-branch, the refs are restored from `refs/original/`.
 pub fn rollback_changes<P: AsRef<Path>>(repo_path: P) -> Result<usize> {
-    restore_original_refs(repo_path)
+    let repo = Repository::open(repo_path.as_ref())?;
+    let restored = if journal::load(&repo)?.is_some() {
+        journal::recover(&repo, false)?
+    } else {
+        restore_original_refs(repo_path.as_ref())?
+    };
+    discard_original_refs(repo_path)?;
+    Ok(restored)
+}
+
+/// Checks the repository can be rewritten safely before a rewrite of
+/// `commits` starts; no commits means a filter over the whole history.
+pub fn preflight<P: AsRef<Path>>(repo_path: P, commits: &[String]) -> Result<PreflightReport> {
+    let commits = commits
+        .iter()
+        .map(|hash| git2::Oid::from_str(hash))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    preflight_check(&Repository::open(repo_path)?, &commits)
+}
+
+/// Drops the `refs/original/` pre-images of a confirmed rewrite; the undo
+/// history keeps its own copy.
+pub fn discard_original_refs<P: AsRef<Path>>(repo_path: P) -> Result<usize> {
+    let repo = Repository::open(repo_path)?;
+    let mut discarded = 0;
+    for reference in repo.references_glob(&format!("{}*", BACKUP_NAMESPACE))? {
+        reference?.delete()?;
+        discarded += 1;
+    }
+    Ok(discarded)
+}
+
+/// Opens the crash journal for an operation about to rewrite history.
+pub fn begin_operation<P: AsRef<Path>>(repo_path: P, operation: &str) -> Result<()> {
+    journal::begin(&Repository::open(repo_path)?, operation)
+}
+
+/// Closes the crash journal once the operation is confirmed or cancelled.
+pub fn finish_operation<P: AsRef<Path>>(repo_path: P) -> Result<()> {
+    journal::finish(&Repository::open(repo_path)?)
+}
+
+/// An operation an earlier run left unfinished.
+pub fn pending_operation<P: AsRef<Path>>(repo_path: P) -> Result<Option<Journal>> {
+    journal::load(&Repository::open(repo_path)?)
+}
+
+/// Completes or rolls back an unfinished operation; returns the refs moved.
+pub fn recover_operation<P: AsRef<Path>>(repo_path: P, complete: bool) -> Result<usize> {
+    journal::recover(&Repository::open(repo_path)?, complete)
+}
+
+/// The undo history, newest first.
+pub fn list_backups<P: AsRef<Path>>(repo_path: P) -> Result<Vec<BackupInfo>> {
+    backups::list_backups(&Repository::open(repo_path)?)
+}
+
+pub fn restore_backup<P: AsRef<Path>>(repo_path: P, id: &str) -> Result<Restored> {
+    backups::restore_backup(&Repository::open(repo_path)?, id)
+}
+
+pub fn delete_backup<P: AsRef<Path>>(repo_path: P, id: &str) -> Result<usize> {
+    backups::delete_backup(&Repository::open(repo_path)?, id)
+}
+
+/// The old-to-new commit map recorded with backup `id`, rendered as `format`.
+pub fn export_commit_map<P: AsRef<Path>>(
+    repo_path: P,
+    id: &str,
+    format: MapFormat,
+) -> Result<String> {
+    let Some(entries) = commit_map::load(&Repository::open(repo_path)?, id)? else {
+        anyhow::bail!("No commit map was recorded for backup {}", id);
+    };
+    commit_map::export(&entries, format)
+}
+
+/// The rewritten branches, from backup `backup`, that have an upstream to
+/// force-push to.
+pub fn push_targets<P: AsRef<Path>>(repo_path: P, backup: &str) -> Result<Vec<PushTarget>> {
+    push::push_targets(&Repository::open(repo_path)?, backup)
+}
+
+pub fn push_branches<P: AsRef<Path>>(
+    repo_path: P,
+    targets: &[PushTarget],
+) -> Result<Vec<PushResult>> {
+    push::push(repo_path, targets)
 }
 
 pub fn get_current_branch<P: AsRef<Path>>(repo_path: P) -> Option<String> {
@@ -62,9 +356,164 @@ pub fn get_current_branch<P: AsRef<Path>>(repo_path: P) -> Option<String> {
         .and_then(|repo| repo.get_current_branch())
 }
 
+/// The identity git would use for new commits (`user.name` / `user.email`).
+pub fn get_user_identity<P: AsRef<Path>>(repo_path: P) -> Option<Identity> {
+    let repo = Repository::open(repo_path).ok()?;
+    let signature = repo.signature().ok()?;
+    Some(identity_of(&signature))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::commands::get_original_refs;
+    use crate::git::test_utils::TestRepo;
+    use crate::models::RefSelection;
+
+    #[test]
+    fn test_rewrite_commits_keeps_full_message() {
+        let repo = TestRepo::new();
+        let target = repo.commit(
+            "Add parser\n\nHandles nested blocks.\n\nSigned-off-by: Test User <test@example.com>\n",
+            &[("a.txt", "1")],
+        );
+
+        let edit = PendingEdit::new(target.to_string(), target.to_string()[..8].to_string())
+            .with_message(
+                "Add block parser\n\nHandles nested blocks.\n\nSigned-off-by: Test User <test@example.com>"
+                    .to_string(),
+            );
+        let preview = rewrite_commits(
+            repo.path(),
+            &[edit],
+            &[],
+            false,
+            Some("main"),
+            &RewriteOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            preview.changes[0].old_message,
+            "Add parser\n\nHandles nested blocks.\n\nSigned-off-by: Test User <test@example.com>"
+        );
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary(), Some("Add block parser"));
+        assert!(head
+            .message()
+            .unwrap()
+            .ends_with("Signed-off-by: Test User <test@example.com>\n"));
+    }
+
+    #[test]
+    fn test_rollback_restores_journaled_refs() {
+        let repo = TestRepo::new();
+        let target = repo.commit("tpyo", &[("a.txt", "1")]);
+        let sig = TestRepo::signature();
+        let notes_ref = "refs/notes/commits";
+        repo.repo
+            .note(&sig, &sig, Some(notes_ref), target, "ci: passed\n", false)
+            .unwrap();
+        let notes_before = repo.repo.refname_to_id(notes_ref).unwrap();
+
+        begin_operation(repo.path(), "Reword 1 commit(s)").unwrap();
+        let edit =
+            PendingEdit::new(target.to_string(), String::new()).with_message("typo".to_string());
+        let options = RewriteOptions {
+            copy_notes: true,
+            ..Default::default()
+        };
+        rewrite_commits(repo.path(), &[edit], &[], false, Some("main"), &options).unwrap();
+        assert_ne!(repo.repo.refname_to_id(notes_ref).unwrap(), notes_before);
+        let history = list_backups(repo.path()).unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].commit_map);
+
+        assert_eq!(rollback_changes(repo.path()).unwrap(), 2);
+        assert_eq!(repo.repo.refname_to_id("refs/heads/main").unwrap(), target);
+        assert_eq!(repo.repo.refname_to_id(notes_ref).unwrap(), notes_before);
+        assert_eq!(pending_operation(repo.path()).unwrap(), None);
+        assert!(get_original_refs(repo.path()).unwrap().is_empty());
+        // the cancelled rewrite leaves nothing in the undo history
+        assert!(list_backups(repo.path()).unwrap().is_empty());
+        assert!(!commit_map::exists(&repo.repo, &history[0].id));
+    }
+
+    #[test]
+    fn test_rewrite_commits_batch_uses_single_backup() {
+        let repo = TestRepo::new();
+        let first = repo.commit("frist", &[("a.txt", "1")]);
+        repo.commit("keep", &[("a.txt", "2")]);
+        let third = repo.commit("thrid", &[("a.txt", "3")]);
+        let tip = repo.commit("tip", &[("a.txt", "4")]);
+
+        let edits = vec![
+            PendingEdit::new(first.to_string(), String::new()).with_message("first".to_string()),
+            PendingEdit::new(third.to_string(), String::new()).with_message("third".to_string()),
+        ];
+        let preview = rewrite_commits(
+            repo.path(),
+            &edits,
+            &[],
+            false,
+            Some("main"),
+            &RewriteOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(preview.changes.len(), 2);
+        assert_eq!(preview.commit_map.len(), 4);
+        assert_eq!(
+            preview.affected_commits,
+            vec!["refs/heads/main".to_string()]
+        );
+
+        let backup = repo
+            .repo
+            .find_reference("refs/original/refs/heads/main")
+            .unwrap();
+        assert_eq!(backup.target(), Some(tip));
+
+        let messages: Vec<String> = {
+            let mut walk = repo.repo.revwalk().unwrap();
+            walk.push_head().unwrap();
+            walk.map(|oid| {
+                let commit = repo.repo.find_commit(oid.unwrap()).unwrap();
+                commit.summary().unwrap().to_string()
+            })
+            .collect()
+        };
+        assert_eq!(messages, vec!["tip", "third", "keep", "first"]);
+    }
+
+    #[test]
+    fn test_rewrite_commits_only_moves_picked_refs() {
+        let repo = TestRepo::new();
+        let base = repo.commit("bsae", &[("a.txt", "1")]);
+        let main_tip = repo.commit("main work", &[("a.txt", "2")]);
+        repo.branch("feature");
+        repo.commit("feature work", &[("b.txt", "1")]);
+        repo.checkout("main");
+
+        let options = RewriteOptions {
+            ref_selection: Some(RefSelection {
+                include: vec!["feat*".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let edit = PendingEdit::new(base.to_string(), String::new()).with_message("base".into());
+        let preview =
+            rewrite_commits(repo.path(), &[edit], &[], true, Some("main"), &options).unwrap();
+
+        assert_eq!(
+            preview.affected_commits,
+            vec!["refs/heads/feature".to_string()]
+        );
+        let main = repo.repo.find_reference("refs/heads/main").unwrap();
+        assert_eq!(main.target(), Some(main_tip));
+    }
 
     #[test]
     fn test_get_current_branch() {
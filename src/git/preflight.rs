@@ -0,0 +1,162 @@
+use crate::git::journal;
+use crate::git::remotes::diverging_remotes;
+use crate::git::rewrite::BACKUP_NAMESPACE;
+use crate::models::PreflightReport;
+use anyhow::Result;
+use git2::{BranchType, Oid, Repository, RepositoryState, Status, StatusOptions};
+
+/// Checks that the repository is in a state a history rewrite can safely
+/// start from, and which remote branches rewriting `commits` would leave
+/// diverged. No commits stands for a tree filter over the whole history.
+pub fn check(repo: &Repository, commits: &[Oid]) -> Result<PreflightReport> {
+    let mut report = PreflightReport::default();
+
+    if let Some(operation) = operation_in_progress(repo.state()) {
+        report.errors.push(format!(
+            "A {} is in progress; finish or abort it first",
+            operation
+        ));
+    }
+
+    if repo.path().join("index.lock").exists() {
+        report.errors.push(
+            "index.lock exists: another git process is running, or one crashed. \
+             Remove .git/index.lock if nothing else is using the repository"
+                .to_string(),
+        );
+    }
+
+    if let Some(pending) = journal::load(repo)? {
+        report.errors.push(format!(
+            "An earlier operation ({}) is unfinished; complete or roll it back first",
+            pending.operation
+        ));
+    }
+
+    match repo.head() {
+        Ok(_) if repo.head_detached()? => report.warnings.push(
+            "HEAD is detached; it will move to the rewritten commit but no branch follows it"
+                .to_string(),
+        ),
+        Ok(_) => {}
+        Err(_) => report
+            .errors
+            .push("The repository has no commits yet".to_string()),
+    }
+
+    if !repo.is_bare() {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .include_ignored(false)
+            .exclude_submodules(true);
+        let statuses = repo.statuses(Some(&mut options))?;
+        let untracked = statuses
+            .iter()
+            .filter(|entry| entry.status() == Status::WT_NEW)
+            .count();
+        let changed = statuses.len() - untracked;
+        if changed > 0 {
+            report.errors.push(format!(
+                "{} file(s) have uncommitted changes; commit or stash them first",
+                changed
+            ));
+        }
+        if untracked > 0 {
+            report.warnings.push(format!(
+                "{} untracked file(s) will be left as they are",
+                untracked
+            ));
+        }
+    }
+
+    let leftovers = repo
+        .references_glob(&format!("{}*", BACKUP_NAMESPACE))?
+        .count();
+    if leftovers > 0 {
+        report.warnings.push(format!(
+            "{} holds {} ref(s) from an unconfirmed or external rewrite; they will be replaced",
+            BACKUP_NAMESPACE, leftovers
+        ));
+    }
+
+    report.diverging = if commits.is_empty() {
+        let mut tips: Vec<Oid> = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .into_iter()
+            .collect();
+        for branch in repo.branches(Some(BranchType::Local))? {
+            tips.extend(branch?.0.get().target());
+        }
+        diverging_remotes(repo, &tips, true)?
+    } else {
+        diverging_remotes(repo, commits, false)?
+    };
+
+    Ok(report)
+}
+
+fn operation_in_progress(state: RepositoryState) -> Option<&'static str> {
+    match state {
+        RepositoryState::Clean => None,
+        RepositoryState::Merge => Some("merge"),
+        RepositoryState::Revert | RepositoryState::RevertSequence => Some("revert"),
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => Some("cherry-pick"),
+        RepositoryState::Bisect => Some("bisect"),
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge => Some("rebase"),
+        RepositoryState::ApplyMailbox | RepositoryState::ApplyMailboxOrRebase => Some("git am"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+
+    #[test]
+    fn test_preflight_reports_unsafe_state() {
+        let repo = TestRepo::new();
+        assert!(check(&repo.repo, &[]).unwrap().is_blocked());
+
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        repo.commit("second", &[("a.txt", "2")]);
+        assert!(check(&repo.repo, &[]).unwrap().is_clear());
+
+        std::fs::write(repo.path().join("a.txt"), "dirty").unwrap();
+        std::fs::write(repo.path().join("notes.txt"), "new").unwrap();
+        repo.repo
+            .reference("refs/original/refs/heads/main", first, false, "test")
+            .unwrap();
+        let report = check(&repo.repo, &[]).unwrap();
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].starts_with("1 file(s) have uncommitted changes"));
+        assert_eq!(report.warnings.len(), 2);
+
+        std::fs::write(repo.path().join("a.txt"), "2").unwrap();
+        std::fs::write(repo.repo.path().join("MERGE_HEAD"), first.to_string()).unwrap();
+        std::fs::write(repo.repo.path().join("index.lock"), "").unwrap();
+        let report = check(&repo.repo, &[]).unwrap();
+        assert_eq!(report.errors.len(), 2);
+        assert!(report.errors[0].contains("merge"));
+
+        let repo = TestRepo::new();
+        let pushed = repo.commit("pushed", &[("a.txt", "1")]);
+        let local = repo.commit("local", &[("a.txt", "2")]);
+        repo.repo
+            .reference("refs/remotes/origin/main", pushed, false, "test")
+            .unwrap();
+        assert!(check(&repo.repo, &[local]).unwrap().is_clear());
+        assert_eq!(
+            check(&repo.repo, &[pushed]).unwrap().diverging,
+            vec!["origin/main"]
+        );
+        assert_eq!(
+            check(&repo.repo, &[]).unwrap().diverging,
+            vec!["origin/main"]
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::git::backups::backup_targets;
+use crate::models::{PushResult, PushTarget};
+use anyhow::Result;
+use git2::{Oid, Repository};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+/// The branches rewritten by the operation saved in backup `backup` that
+/// have an upstream to replace. Each lease is the upstream's remote-tracking
+/// hash from before the rewrite, since an all-branches rewrite moves the
+/// remote-tracking refs too. Branches whose upstream was never fetched are
+/// left out, as there is nothing to lease against.
+pub fn push_targets(repo: &Repository, backup: &str) -> Result<Vec<PushTarget>> {
+    let before: BTreeMap<String, Oid> = backup_targets(repo, backup)?.into_iter().collect();
+    let config = repo.config()?.snapshot()?;
+
+    let mut targets = Vec::new();
+    for (branch, old) in &before {
+        if !branch.starts_with("refs/heads/") {
+            continue;
+        }
+        let Ok(new) = repo.refname_to_id(branch) else {
+            continue;
+        };
+        if new == *old {
+            continue;
+        }
+        let (Ok(remote), Ok(remote_ref), Ok(tracking)) = (
+            repo.branch_upstream_remote(branch),
+            config.get_string(&format!(
+                "branch.{}.merge",
+                branch.trim_start_matches("refs/heads/")
+            )),
+            repo.branch_upstream_name(branch),
+        ) else {
+            continue;
+        };
+        let (Some(remote), Some(tracking)) = (remote.as_str(), tracking.as_str()) else {
+            continue;
+        };
+        let Some(lease) = before
+            .get(tracking)
+            .copied()
+            .or_else(|| repo.refname_to_id(tracking).ok())
+        else {
+            continue;
+        };
+        if lease == new {
+            continue;
+        }
+        targets.push(PushTarget {
+            branch: branch.clone(),
+            remote: remote.to_string(),
+            remote_ref,
+            lease: lease.to_string(),
+            new: new.to_string(),
+        });
+    }
+    Ok(targets)
+}
+
+/// Force-pushes every target with an explicit lease, one `git push` per
+/// remote, and reports what happened to each ref. A remote branch that no
+/// longer matches its lease is left alone.
+pub fn push<P: AsRef<Path>>(repo_path: P, targets: &[PushTarget]) -> Result<Vec<PushResult>> {
+    let mut by_remote: BTreeMap<&str, Vec<&PushTarget>> = BTreeMap::new();
+    for target in targets {
+        by_remote.entry(&target.remote).or_default().push(target);
+    }
+
+    let mut results = Vec::new();
+    for (remote, targets) in by_remote {
+        let mut command = Command::new("git");
+        command
+            .current_dir(repo_path.as_ref())
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .args(["push", "--porcelain"]);
+        for target in &targets {
+            command.arg(format!(
+                "--force-with-lease={}:{}",
+                target.remote_ref, target.lease
+            ));
+        }
+        command.arg(remote);
+        for target in &targets {
+            command.arg(format!("{}:{}", target.new, target.remote_ref));
+        }
+        let output = command.output()?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reported = parse_porcelain(&stdout);
+        for target in targets {
+            let (accepted, message) = match reported.get(target.remote_ref.as_str()) {
+                Some((flag, summary)) => describe(*flag, summary),
+                None => (
+                    false,
+                    stderr
+                        .lines()
+                        .rev()
+                        .find(|line| !line.trim().is_empty())
+                        .unwrap_or("git push failed")
+                        .trim()
+                        .to_string(),
+                ),
+            };
+            results.push(PushResult {
+                target: target.clone(),
+                accepted,
+                message,
+            });
+        }
+    }
+    Ok(results)
+}
+
+/// Flag and summary per destination ref from `git push --porcelain`, whose
+/// ref lines read `<flag>\t<src>:<dst>\t<summary>`.
+fn parse_porcelain(stdout: &str) -> BTreeMap<&str, (char, &str)> {
+    let mut reported = BTreeMap::new();
+    for line in stdout.lines() {
+        let mut fields = line.split('\t');
+        let (Some(flag), Some(refspec), Some(summary)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let (Some(flag), Some((_, dst))) = (flag.chars().next(), refspec.rsplit_once(':')) else {
+            continue;
+        };
+        reported.insert(dst, (flag, summary));
+    }
+    reported
+}
+
+fn describe(flag: char, summary: &str) -> (bool, String) {
+    match flag {
+        '!' if summary.contains("stale info") => (
+            false,
+            "Refused: the remote branch moved since the last fetch; fetch and review it first"
+                .to_string(),
+        ),
+        '!' => (false, format!("Rejected {}", summary)),
+        '=' => (true, "Already up to date".to_string()),
+        _ => (true, summary.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::backups::save_backup;
+    use crate::git::test_utils::TestRepo;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_push_uses_pre_rewrite_lease() {
+        let remote_dir = TempDir::new().unwrap();
+        let bare = Repository::init_bare(remote_dir.path()).unwrap();
+        let repo = TestRepo::new();
+        let base = repo.commit("base", &[("a.txt", "1")]);
+        let old = repo.commit("old", &[("a.txt", "2")]);
+        repo.repo
+            .remote("origin", &format!("file://{}", remote_dir.path().display()))
+            .unwrap();
+        let status = Command::new("git")
+            .current_dir(repo.path())
+            .args(["push", "--quiet", "-u", "origin", "main"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let rewrite = |from: Oid, message: &str| {
+            let parent = repo.repo.find_commit(base).unwrap();
+            let sig = TestRepo::signature();
+            let new = repo
+                .repo
+                .commit(
+                    None,
+                    &sig,
+                    &sig,
+                    message,
+                    &parent.tree().unwrap(),
+                    &[&parent],
+                )
+                .unwrap();
+            let main = "refs/heads/main".to_string();
+            let tracking = "refs/remotes/origin/main".to_string();
+            let tracked = repo.repo.refname_to_id(&tracking).unwrap();
+            let id = save_backup(
+                &repo.repo,
+                &[(main.clone(), from), (tracking.clone(), tracked)],
+            )
+            .unwrap();
+            repo.repo.reference(&main, new, true, "test").unwrap();
+            // an all-branches rewrite moves the remote-tracking ref as well
+            repo.repo.reference(&tracking, new, true, "test").unwrap();
+            (id, new)
+        };
+
+        let (id, new) = rewrite(old, "rewritten");
+        let targets = push_targets(&repo.repo, &id).unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].remote_name(), "origin/main");
+        assert_eq!(targets[0].lease, old.to_string());
+
+        let results = push(repo.path(), &targets).unwrap();
+        assert!(results[0].accepted, "{}", results[0].message);
+        assert_eq!(bare.refname_to_id("refs/heads/main").unwrap(), new);
+
+        // someone else pushes before the next rewrite is sent
+        bare.reference("refs/heads/main", base, true, "test")
+            .unwrap();
+        let (id, _) = rewrite(new, "rewritten again");
+        let targets = push_targets(&repo.repo, &id).unwrap();
+        let results = push(repo.path(), &targets).unwrap();
+        assert!(!results[0].accepted);
+        assert!(results[0].message.contains("moved"));
+        assert_eq!(bare.refname_to_id("refs/heads/main").unwrap(), base);
+    }
+}
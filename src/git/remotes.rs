@@ -0,0 +1,135 @@
+use anyhow::Result;
+use git2::{BranchType, Oid, Repository};
+use std::collections::{HashMap, HashSet};
+
+/// Remote-tracking branches by short name (`origin/main`) and tip, leaving
+/// out symbolic ones such as `origin/HEAD`.
+pub fn remote_branches(repo: &Repository) -> Result<Vec<(String, Oid)>> {
+    let mut branches = Vec::new();
+    for branch in repo.branches(Some(BranchType::Remote))? {
+        let (branch, _) = branch?;
+        let reference = branch.get();
+        if reference.symbolic_target().is_some() {
+            continue;
+        }
+        let (Some(name), Some(tip)) = (branch.name()?, reference.target()) else {
+            continue;
+        };
+        branches.push((name.to_string(), tip));
+    }
+    Ok(branches)
+}
+
+/// For each of `commits`, the remote-tracking branches in `remotes` that
+/// already contain it. Each tip is walked once, stopping below the commits'
+/// merge base since nothing older can be one of them.
+pub fn pushed_to(
+    repo: &Repository,
+    remotes: &[(String, Oid)],
+    commits: &[Oid],
+) -> Result<HashMap<Oid, Vec<String>>> {
+    let mut pushed: HashMap<Oid, Vec<String>> = HashMap::new();
+    if commits.is_empty() {
+        return Ok(pushed);
+    }
+    let wanted: HashSet<Oid> = commits.iter().copied().collect();
+    // folding pairwise bases gives a commit every wanted one descends from
+    let floor = commits[1..]
+        .iter()
+        .try_fold(commits[0], |base, commit| repo.merge_base(base, *commit))
+        .ok();
+
+    for (name, tip) in remotes {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(*tip)?;
+        // the base's parents are strict ancestors of every wanted commit
+        if let Some(floor) = floor {
+            for parent in repo.find_commit(floor)?.parent_ids() {
+                revwalk.hide(parent)?;
+            }
+        }
+        for oid in revwalk {
+            let oid = oid?;
+            if wanted.contains(&oid) {
+                pushed.entry(oid).or_default().push(name.clone());
+            }
+        }
+    }
+    Ok(pushed)
+}
+
+/// The remote-tracking branches a rewrite would leave diverged: those
+/// containing any of `commits`, or with `whole_history` (a tree filter may
+/// change any commit) those sharing any history with them.
+pub fn diverging_remotes(
+    repo: &Repository,
+    commits: &[Oid],
+    whole_history: bool,
+) -> Result<Vec<String>> {
+    let remotes = remote_branches(repo)?;
+    let mut diverging = Vec::new();
+    for (name, tip) in &remotes {
+        let affected = commits.iter().any(|commit| {
+            if whole_history {
+                repo.merge_base(*tip, *commit).is_ok()
+            } else {
+                tip == commit || repo.graph_descendant_of(*tip, *commit).unwrap_or(false)
+            }
+        });
+        if affected {
+            diverging.push(name.clone());
+        }
+    }
+    Ok(diverging)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+
+    #[test]
+    fn test_pushed_commits_are_found_from_remote_refs() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        let pushed = repo.commit("pushed", &[("a.txt", "2")]);
+        let local = repo.commit("local", &[("a.txt", "3")]);
+        repo.repo
+            .reference("refs/remotes/origin/main", pushed, false, "test")
+            .unwrap();
+        repo.repo
+            .reference("refs/remotes/fork/old", first, false, "test")
+            .unwrap();
+        repo.repo
+            .reference_symbolic(
+                "refs/remotes/origin/HEAD",
+                "refs/remotes/origin/main",
+                false,
+                "test",
+            )
+            .unwrap();
+
+        let remotes = remote_branches(&repo.repo).unwrap();
+        assert_eq!(remotes.len(), 2);
+        let found = pushed_to(&repo.repo, &remotes, &[local, pushed, first]).unwrap();
+        assert_eq!(found[&first], vec!["fork/old", "origin/main"]);
+        assert_eq!(found[&pushed], vec!["origin/main"]);
+        assert!(!found.contains_key(&local));
+
+        // a page that starts above the oldest commit still sees it pushed
+        let found = pushed_to(&repo.repo, &remotes, &[pushed]).unwrap();
+        assert_eq!(found[&pushed], vec!["origin/main"]);
+
+        assert!(diverging_remotes(&repo.repo, &[local], false)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            diverging_remotes(&repo.repo, &[pushed], false).unwrap(),
+            vec!["origin/main"]
+        );
+        assert_eq!(
+            diverging_remotes(&repo.repo, &[local], true).unwrap().len(),
+            2
+        );
+    }
+}
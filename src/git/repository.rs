@@ -1,12 +1,16 @@
-use crate::models::CommitInfo;
+use crate::git::remotes;
+use crate::git::rewrite::{self, RewriteOutcome, RewritePlan};
+use crate::models::{CommitInfo, CommitTime, Identity, RefEntry};
 use anyhow::Result;
-use chrono::{DateTime, Utc};
-use git2::Repository;
+use git2::{Oid, Repository};
 use std::path::Path;
 
 pub trait GitRepository {
     fn load_commits(&self, limit: usize, offset: usize) -> Result<Vec<CommitInfo>>;
     fn get_current_branch(&self) -> Option<String>;
+    fn rewrite_refs(&self, all_branches: bool, branch_name: Option<&str>) -> Result<Vec<String>>;
+    fn list_refs(&self) -> Result<Vec<RefEntry>>;
+    fn rewrite_history(&self, plan: &RewritePlan) -> Result<RewriteOutcome>;
 }
 
 pub struct GitRepositoryImpl {
@@ -30,7 +34,9 @@ impl GitRepository for GitRepositoryImpl {
         revwalk.push_head()?;
 
         let mut commits = Vec::new();
+        let mut oids: Vec<Oid> = Vec::new();
         let end_index = offset + limit;
+        let remote_branches = remotes::remote_branches(&self.repo)?;
 
         for (i, oid) in revwalk.enumerate() {
             if i >= end_index {
@@ -43,26 +49,30 @@ impl GitRepository for GitRepositoryImpl {
             let oid = oid?;
             let commit = self.repo.find_commit(oid)?;
 
-            let message = commit
-                .message()
-                .unwrap_or("(no message)")
-                .lines()
-                .next()
-                .unwrap_or("(no message)")
-                .to_string();
+            let raw_message = String::from_utf8_lossy(commit.message_raw_bytes()).to_string();
 
-            let time = commit.time();
-            let datetime = DateTime::<Utc>::from_timestamp(time.seconds(), 0).unwrap_or_default();
-            let date_str = datetime.format("%Y-%m-%d %H:%M").to_string();
-
-            let author = commit.author().name().unwrap_or("Unknown").to_string();
+            let author = identity_of(&commit.author());
+            let committer = identity_of(&commit.committer());
 
             let hash_str = oid.to_string();
             let short_hash = hash_str.chars().take(8).collect::<String>();
 
-            commits.push(CommitInfo::new(
-                hash_str, short_hash, message, date_str, author,
-            ));
+            let info = CommitInfo::new(
+                hash_str,
+                short_hash,
+                raw_message,
+                author,
+                time_of(&commit.author()),
+                committer,
+                time_of(&commit.committer()),
+            );
+            commits.push(info);
+            oids.push(oid);
+        }
+
+        let mut pushed = remotes::pushed_to(&self.repo, &remote_branches, &oids)?;
+        for (info, oid) in commits.iter_mut().zip(&oids) {
+            info.pushed_to = pushed.remove(oid).unwrap_or_default();
         }
 
         Ok(commits)
@@ -74,6 +84,30 @@ impl GitRepository for GitRepositoryImpl {
             Err(_) => None,
         }
     }
+
+    fn rewrite_refs(&self, all_branches: bool, branch_name: Option<&str>) -> Result<Vec<String>> {
+        rewrite::collect_rewrite_refs(&self.repo, all_branches, branch_name)
+    }
+
+    fn list_refs(&self) -> Result<Vec<RefEntry>> {
+        rewrite::list_refs(&self.repo)
+    }
+
+    fn rewrite_history(&self, plan: &RewritePlan) -> Result<RewriteOutcome> {
+        rewrite::rewrite_history(&self.repo, plan)
+    }
+}
+
+pub fn identity_of(signature: &git2::Signature) -> Identity {
+    Identity::new(
+        signature.name().unwrap_or("Unknown").to_string(),
+        signature.email().unwrap_or("").to_string(),
+    )
+}
+
+pub fn time_of(signature: &git2::Signature) -> CommitTime {
+    let when = signature.when();
+    CommitTime::new(when.seconds(), when.offset_minutes())
 }
 
 #[cfg(test)]
@@ -0,0 +1,1758 @@
+use crate::git::backups::{self, APP_REF_ROOT};
+use crate::git::commit_map;
+use crate::git::journal;
+use crate::git::notes::{self, NotesUpdate, NOTES_NAMESPACE};
+use crate::git::signing::{self, Signer};
+use crate::git::tags::{self, TagUpdate};
+use crate::git::tree_filter::{TreeFilter, TreeRewriter};
+use crate::models::{CommitTime, JournalPhase, RefEntry, RefKind, SignatureEdit};
+use anyhow::{Context, Result};
+use git2::build::CheckoutBuilder;
+use git2::{Oid, Repository, Signature, Sort};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+pub const BACKUP_NAMESPACE: &str = "refs/original/";
+const TAG_NAMESPACE: &str = "refs/tags/";
+
+/// Whether `name` holds a pre-image, from `refs/original/` or the undo
+/// history, or is one of the app's scratch refs, rather than live history.
+pub fn is_backup_ref(name: &str) -> bool {
+    name.starts_with(BACKUP_NAMESPACE) || name.starts_with(APP_REF_ROOT)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CommitEdit {
+    pub message: Option<String>,
+    pub author: SignatureEdit,
+    pub committer: SignatureEdit,
+    /// Newer commits folded into this one, oldest first. The result keeps
+    /// this commit's parents and takes the tree of the last of them.
+    pub squash: Vec<Oid>,
+    /// Removes the commit; its descendants are replayed onto its parent.
+    pub drop: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RewritePlan {
+    pub edits: HashMap<Oid, CommitEdit>,
+    pub refs: Vec<String>,
+    /// Signs every recreated commit when set; otherwise signatures are dropped.
+    pub signer: Option<Signer>,
+    /// A new order for a linear run of commits, oldest first. The run is
+    /// replayed onto the parent of its oldest commit.
+    pub order: Vec<Oid>,
+    /// Content changes applied to every commit on `refs`.
+    pub tree_filter: TreeFilter,
+    /// Moves every tag whose commit was rewritten, not just tags in `refs`.
+    pub rewrite_tags: bool,
+    /// Re-signs recreated tags that were signed.
+    pub tag_signer: Option<Signer>,
+    /// Notes refs whose notes are copied to the rewritten commits.
+    pub notes_refs: Vec<String>,
+    /// Replaces hashes of rewritten commits quoted in later messages with
+    /// their new hashes, keeping each abbreviation's length.
+    pub update_hash_refs: bool,
+}
+
+/// A commit hash quoted in a message and what it was replaced with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HashRefUpdate {
+    /// The commit whose message quoted the hash.
+    pub commit: Oid,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RefUpdate {
+    pub name: String,
+    pub old_oid: Oid,
+    pub new_oid: Oid,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RewriteOutcome {
+    pub commit_map: HashMap<Oid, Oid>,
+    pub ref_updates: Vec<RefUpdate>,
+    /// Originally signed commits whose rewritten copy carries no signature.
+    pub lost_signatures: Vec<Oid>,
+    /// Commits left out because the tree filter emptied them.
+    pub pruned: Vec<Oid>,
+    /// Distinct blobs the tree filter removed, and their combined size.
+    pub stripped_blobs: usize,
+    pub stripped_bytes: u64,
+    /// Distinct blobs whose text the tree filter rewrote.
+    pub replaced_blobs: usize,
+    /// Tags moved to rewritten commits; also listed in `ref_updates`.
+    pub tags: Vec<TagUpdate>,
+    /// Notes refs that gained copied notes; also listed in `ref_updates`.
+    pub notes: Vec<NotesUpdate>,
+    /// The undo-history backup holding the refs' old positions.
+    pub backup: Option<String>,
+    /// Hashes substituted in rewritten messages, see `RewritePlan::update_hash_refs`.
+    pub hash_refs: Vec<HashRefUpdate>,
+}
+
+/// Lists the refs a rewrite should move: every branch-like ref when
+/// `all_branches` is set, otherwise only the given (or current) branch.
+pub fn collect_rewrite_refs(
+    repo: &Repository,
+    all_branches: bool,
+    branch_name: Option<&str>,
+) -> Result<Vec<String>> {
+    if !all_branches {
+        if let Some(branch) = branch_name {
+            let reference = repo.resolve_reference_from_short_name(branch)?;
+            let name = reference.name().unwrap_or("HEAD").to_string();
+            return Ok(vec![name]);
+        }
+    }
+
+    let mut refs = Vec::new();
+    for reference in repo.references()? {
+        let reference = reference?;
+        if reference.symbolic_target().is_some() {
+            continue;
+        }
+        if let Some(name) = reference.name() {
+            // tags and notes follow their commits separately, see
+            // `RewritePlan::rewrite_tags` and `RewritePlan::notes_refs`
+            if is_backup_ref(name)
+                || name.starts_with(TAG_NAMESPACE)
+                || name.starts_with(NOTES_NAMESPACE)
+            {
+                continue;
+            }
+            refs.push(name.to_string());
+        }
+    }
+
+    Ok(refs)
+}
+
+/// Every ref a rewrite could move, for the ref picker: backups, symbolic
+/// refs, notes refs and refs that don't lead to a commit are left out.
+pub fn list_refs(repo: &Repository) -> Result<Vec<RefEntry>> {
+    let mut entries = Vec::new();
+    for reference in repo.references()? {
+        let reference = reference?;
+        if reference.symbolic_target().is_some() {
+            continue;
+        }
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        if is_backup_ref(name) || name.starts_with(NOTES_NAMESPACE) {
+            continue;
+        }
+        let Ok(commit) = reference.peel_to_commit() else {
+            continue;
+        };
+        entries.push(RefEntry {
+            name: name.to_string(),
+            short_name: reference.shorthand().unwrap_or(name).to_string(),
+            kind: RefKind::of(name),
+            target: short(commit.id()),
+        });
+    }
+    entries.sort_by(|a, b| (a.kind, &a.name).cmp(&(b.kind, &b.name)));
+    Ok(entries)
+}
+
+/// Recreates the edited commits and all of their descendants reachable from
+/// `plan.refs`, then moves those refs. Pre-images are kept under
+/// `refs/original/` so the rewrite can be rolled back.
+pub fn rewrite_history(repo: &Repository, plan: &RewritePlan) -> Result<RewriteOutcome> {
+    let tips = resolve_tips(repo, &plan.refs)?;
+    if tips.is_empty() {
+        anyhow::bail!("No refs to rewrite");
+    }
+
+    let folded = folded_commits(repo, &plan.edits)?;
+    let reorder = Reorder::prepare(repo, plan, &folded)?;
+    let mut trees = TreeRewriter::new(&plan.tree_filter)?;
+
+    let mut targets: Vec<Oid> = plan.edits.keys().copied().collect();
+    targets.extend(reorder.iter().flat_map(|r| r.original.iter().copied()));
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    for (_, tip) in &tips {
+        revwalk.push(*tip)?;
+    }
+    // a tree filter touches every commit, so the whole history is walked
+    if !trees.is_active() {
+        for oid in boundary_commits(repo, &targets)? {
+            revwalk.hide(oid)?;
+        }
+    }
+
+    let mut commit_map: HashMap<Oid, Oid> = HashMap::new();
+    // where children of a commit now attach, when that differs from its copy
+    let mut successor: HashMap<Oid, Oid> = HashMap::new();
+    let mut visited: HashSet<Oid> = HashSet::new();
+    let mut lost_signatures = Vec::new();
+    let mut pruned = Vec::new();
+    let mut dropped = Vec::new();
+    let mut hash_refs = Vec::new();
+
+    for oid in revwalk {
+        let oid = oid?;
+        visited.insert(oid);
+
+        // recreated together with the head of its squash
+        if folded.contains_key(&oid) {
+            continue;
+        }
+
+        if let Some(reorder) = reorder.as_ref().filter(|r| r.members.contains(&oid)) {
+            if oid == reorder.original[0] {
+                let end = reorder.replay(
+                    repo,
+                    plan,
+                    &mut trees,
+                    &mut commit_map,
+                    &successor,
+                    &mut lost_signatures,
+                    &mut hash_refs,
+                )?;
+                successor.insert(reorder.tip(), end);
+            }
+            continue;
+        }
+
+        let commit = repo.find_commit(oid)?;
+        let edit = plan.edits.get(&oid);
+
+        let old_parents: Vec<Oid> = commit.parent_ids().collect();
+        for parent in &old_parents {
+            if let Some(last) = folded.get(parent).filter(|last| *last != parent) {
+                anyhow::bail!(
+                    "Cannot squash {} into {}: commit {} also builds on it",
+                    parent,
+                    last,
+                    oid
+                );
+            }
+            let moved = reorder
+                .as_ref()
+                .is_some_and(|r| r.members.contains(parent) && *parent != r.tip());
+            if moved {
+                anyhow::bail!(
+                    "Cannot reorder {}: commit {} also builds on it",
+                    short(*parent),
+                    short(oid)
+                );
+            }
+        }
+        let new_parents: Vec<Oid> = old_parents
+            .iter()
+            .map(|p| new_location(*p, &commit_map, &successor))
+            .collect();
+
+        if edit.is_some_and(|e| e.drop) {
+            let [parent] = new_parents[..] else {
+                anyhow::bail!(
+                    "Cannot drop {}: only commits with a single parent can be dropped",
+                    short(oid)
+                );
+            };
+            successor.insert(oid, parent);
+            dropped.push(oid);
+            continue;
+        }
+
+        let tree = match edit.and_then(|e| e.squash.last()) {
+            Some(last) => squashed_tree(repo, &mut trees, &commit, *last, &new_parents)?,
+            None => replayed_tree(repo, &mut trees, &commit, &new_parents)?,
+        };
+
+        let (message, substituted) = rewritten_message(repo, plan, &commit, &commit_map);
+        if edit.is_none()
+            && substituted.is_empty()
+            && new_parents == old_parents
+            && tree == commit.tree_id()
+        {
+            continue;
+        }
+
+        if plan.tree_filter.prune_empty && edit.is_none() {
+            if let ([old_parent], [new_parent]) = (&old_parents[..], &new_parents[..]) {
+                let was_empty = repo.find_commit(*old_parent)?.tree_id() == commit.tree_id();
+                let is_empty = repo.find_commit(*new_parent)?.tree_id() == tree;
+                if is_empty && !was_empty {
+                    successor.insert(oid, *new_parent);
+                    pruned.push(oid);
+                    continue;
+                }
+            }
+        }
+
+        let new_oid = recreate_commit(repo, plan, &commit, &message, tree, &new_parents)?;
+        hash_refs.extend(substituted);
+
+        if new_oid != oid {
+            if plan.signer.is_none() && signing::is_signed(&commit) {
+                lost_signatures.push(oid);
+            }
+            commit_map.insert(oid, new_oid);
+        }
+        for member in edit.map(|e| e.squash.as_slice()).unwrap_or_default() {
+            commit_map.insert(*member, new_oid);
+        }
+    }
+
+    for target in &targets {
+        if !visited.contains(target) {
+            anyhow::bail!("Commit {} is not reachable from the selected refs", target);
+        }
+    }
+
+    let locate = |oid| new_location(oid, &commit_map, &successor);
+    let tag_names: Vec<String> = if plan.rewrite_tags {
+        repo.references_glob("refs/tags/*")?
+            .filter_map(|reference| reference.ok()?.name().map(str::to_string))
+            .collect()
+    } else {
+        plan.refs
+            .iter()
+            .filter(|name| name.starts_with(TAG_NAMESPACE))
+            .cloned()
+            .collect()
+    };
+    let tags = tags::rewrite_tags(repo, &tag_names, locate, plan.tag_signer.as_ref())?;
+
+    let mut ref_updates: Vec<RefUpdate> = tips
+        .into_iter()
+        // tags are moved below, keeping annotated ones annotated
+        .filter(|(name, _)| !name.starts_with(TAG_NAMESPACE))
+        .filter_map(|(name, old_oid)| {
+            let new_oid = new_location(old_oid, &commit_map, &successor);
+            (new_oid != old_oid).then_some(RefUpdate {
+                name,
+                old_oid,
+                new_oid,
+            })
+        })
+        .collect();
+    ref_updates.extend(tags.iter().map(|tag| RefUpdate {
+        name: tag.name.clone(),
+        old_oid: tag.old_oid,
+        new_oid: tag.new_oid,
+    }));
+
+    // notes refs rewritten as history of their own keep their notes as is
+    let notes_refs: Vec<String> = plan
+        .notes_refs
+        .iter()
+        .filter(|name| !plan.refs.contains(name))
+        .cloned()
+        .collect();
+    let notes = notes::copy_notes(repo, &notes_refs, &commit_map)?;
+    ref_updates.extend(notes.iter().map(|update| RefUpdate {
+        name: update.name.clone(),
+        old_oid: update.old_oid,
+        new_oid: update.new_oid,
+    }));
+
+    let backup = update_refs(repo, &ref_updates)?;
+    if let Some(id) = &backup {
+        // a reorder's tip is in `successor` too, but it was moved, not removed
+        let removed: Vec<Oid> = dropped.iter().chain(&pruned).copied().collect();
+        commit_map::save(repo, id, &commit_map, &removed)?;
+    }
+
+    let (stripped_blobs, stripped_bytes) = trees.stripped();
+    Ok(RewriteOutcome {
+        commit_map,
+        ref_updates,
+        lost_signatures,
+        pruned,
+        stripped_blobs,
+        stripped_bytes,
+        replaced_blobs: trees.replaced(),
+        tags,
+        notes,
+        backup,
+        hash_refs,
+    })
+}
+
+/// The tree for a commit moving onto `new_parents`: its own (filtered)
+/// snapshot while its first parent's content is what filtering alone would
+/// produce, otherwise its changes replayed on the new first parent.
+fn replayed_tree(
+    repo: &Repository,
+    trees: &mut TreeRewriter,
+    commit: &git2::Commit,
+    new_parents: &[Oid],
+) -> Result<Oid> {
+    let own = trees.rewrite(repo, commit.tree_id())?;
+    let (Some(old_parent), Some(new_parent)) = (commit.parent_ids().next(), new_parents.first())
+    else {
+        return Ok(own);
+    };
+    let expected = trees.rewrite(repo, repo.find_commit(old_parent)?.tree_id())?;
+    if expected == repo.find_commit(*new_parent)?.tree_id() {
+        return Ok(own);
+    }
+    if commit.parent_count() > 1 {
+        anyhow::bail!(
+            "Cannot replay merge commit {} onto a changed parent",
+            short(commit.id())
+        );
+    }
+    cherry_pick_tree(repo, trees, commit, *new_parent)
+}
+
+/// The tree for the head of a squash whose last member is `last`: that
+/// member's own tree while the group stays in place, otherwise the group's
+/// combined change replayed onto the new parent, so commits dropped or moved
+/// below it don't come back.
+fn squashed_tree(
+    repo: &Repository,
+    trees: &mut TreeRewriter,
+    head: &git2::Commit,
+    last: Oid,
+    new_parents: &[Oid],
+) -> Result<Oid> {
+    let last_tree = repo.find_commit(last)?.tree_id();
+    if head.parent_ids().eq(new_parents.iter().copied()) {
+        return trees.rewrite(repo, last_tree);
+    }
+    let [new_parent] = new_parents[..] else {
+        anyhow::bail!(
+            "Cannot replay merge commit {} onto a changed parent",
+            short(head.id())
+        );
+    };
+    apply_change(
+        repo,
+        trees,
+        head,
+        head.parent(0)?.tree_id(),
+        last_tree,
+        new_parent,
+    )
+}
+
+/// Where a commit's children and refs should point after the rewrite.
+fn new_location(oid: Oid, commit_map: &HashMap<Oid, Oid>, successor: &HashMap<Oid, Oid>) -> Oid {
+    successor
+        .get(&oid)
+        .or_else(|| commit_map.get(&oid))
+        .copied()
+        .unwrap_or(oid)
+}
+
+/// Writes a copy of `commit` with the plan's edits for it applied, on top of
+/// `parents` and with `tree`, signing it if the plan asks for that.
+fn recreate_commit(
+    repo: &Repository,
+    plan: &RewritePlan,
+    commit: &git2::Commit,
+    message: &str,
+    tree: Oid,
+    parents: &[Oid],
+) -> Result<Oid> {
+    let oid = commit.id();
+    let edit = plan.edits.get(&oid);
+
+    let (author, committer) = match edit {
+        Some(edit) => (
+            edit_signature(&commit.author(), &edit.author)?,
+            edit_signature(&commit.committer(), &edit.committer)?,
+        ),
+        None => (commit.author(), commit.committer()),
+    };
+
+    let tree = repo.find_tree(tree)?;
+    let parents = parents
+        .iter()
+        .map(|p| repo.find_commit(*p))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+
+    match &plan.signer {
+        Some(signer) => {
+            let buffer =
+                repo.commit_create_buffer(&author, &committer, message, &tree, &parent_refs)?;
+            let payload = std::str::from_utf8(&buffer)
+                .with_context(|| format!("Commit {} is not valid UTF-8", oid))?;
+            let signature = signer
+                .sign(payload)
+                .with_context(|| format!("Failed to sign rewritten commit {}", oid))?;
+            repo.commit_signed(payload, &signature, None)
+        }
+        None => repo.commit(None, &author, &committer, message, &tree, &parent_refs),
+    }
+    .with_context(|| format!("Failed to recreate commit {}", oid))
+}
+
+/// The message for the copy of `commit`: its edited or original one, with
+/// hashes of commits already in `commit_map` updated when the plan asks for
+/// that. Also returns the substitutions made.
+fn rewritten_message(
+    repo: &Repository,
+    plan: &RewritePlan,
+    commit: &git2::Commit,
+    commit_map: &HashMap<Oid, Oid>,
+) -> (String, Vec<HashRefUpdate>) {
+    let message = match plan
+        .edits
+        .get(&commit.id())
+        .and_then(|e| e.message.as_deref())
+    {
+        Some(message) => normalize_message(message),
+        None => String::from_utf8_lossy(commit.message_raw_bytes()).to_string(),
+    };
+    if !plan.update_hash_refs || commit_map.is_empty() {
+        return (message, Vec::new());
+    }
+
+    static HASH: OnceLock<Regex> = OnceLock::new();
+    let hash = HASH.get_or_init(|| Regex::new(r"\b[0-9a-f]{7,40}\b").expect("hash pattern"));
+    let mut substituted = Vec::new();
+    let message = hash
+        .replace_all(&message, |caps: &regex::Captures| {
+            let quoted = &caps[0];
+            // digit-only runs are far more likely dates or issue numbers
+            if !quoted.bytes().any(|b| b.is_ascii_alphabetic()) {
+                return quoted.to_string();
+            }
+            // ambiguous or unknown prefixes, and commits that kept their
+            // hash, stay as they are
+            let Some(new) = repo
+                .find_commit_by_prefix(quoted)
+                .ok()
+                .and_then(|found| commit_map.get(&found.id()).copied())
+            else {
+                return quoted.to_string();
+            };
+            let new = abbreviate(repo, new, quoted.len());
+            substituted.push(HashRefUpdate {
+                commit: commit.id(),
+                old: quoted.to_string(),
+                new: new.clone(),
+            });
+            new
+        })
+        .into_owned();
+    (message, substituted)
+}
+
+/// The shortest prefix of `oid`, at least `min_len` long, that names no
+/// other object.
+fn abbreviate(repo: &Repository, oid: Oid, min_len: usize) -> String {
+    let hex = oid.to_string();
+    (min_len..hex.len())
+        .find(|len| {
+            repo.find_object_by_prefix(&hex[..*len], None)
+                .is_ok_and(|found| found.id() == oid)
+        })
+        .map_or(hex.clone(), |len| hex[..len].to_string())
+}
+
+/// A linear run of commits to be replayed in a new order.
+struct Reorder {
+    /// The run as it is today, oldest first.
+    original: Vec<Oid>,
+    /// The wanted order, oldest first.
+    order: Vec<Oid>,
+    members: HashSet<Oid>,
+}
+
+impl Reorder {
+    fn prepare(
+        repo: &Repository,
+        plan: &RewritePlan,
+        folded: &HashMap<Oid, Oid>,
+    ) -> Result<Option<Self>> {
+        if plan.order.len() < 2 {
+            return Ok(None);
+        }
+
+        let members: HashSet<Oid> = plan.order.iter().copied().collect();
+        if members.len() != plan.order.len() {
+            anyhow::bail!("The new order lists a commit twice");
+        }
+        for member in &members {
+            let squashing = plan.edits.get(member).is_some_and(|e| !e.squash.is_empty());
+            if squashing || folded.contains_key(member) {
+                anyhow::bail!(
+                    "Commit {} cannot be squashed and moved at once",
+                    short(*member)
+                );
+            }
+            if plan.edits.get(member).is_some_and(|e| e.drop) {
+                anyhow::bail!(
+                    "Commit {} cannot be dropped and moved at once",
+                    short(*member)
+                );
+            }
+        }
+
+        // the run's tip is the one member no other member builds on
+        let mut tips = Vec::new();
+        for member in &members {
+            let commit = repo.find_commit(*member)?;
+            if commit.parent_count() != 1 {
+                anyhow::bail!("Cannot reorder merge commit {}", short(*member));
+            }
+            tips.push(*member);
+        }
+        for member in &members {
+            let parent = repo.find_commit(*member)?.parent_id(0)?;
+            tips.retain(|tip| *tip != parent);
+        }
+        let [tip] = tips[..] else {
+            anyhow::bail!("Commits to reorder must be one unbroken run of history");
+        };
+
+        let mut original = vec![tip];
+        while original.len() < members.len() {
+            let parent = repo
+                .find_commit(*original.last().unwrap_or(&tip))?
+                .parent_id(0)?;
+            if !members.contains(&parent) {
+                anyhow::bail!("Commits to reorder must be one unbroken run of history");
+            }
+            original.push(parent);
+        }
+        original.reverse();
+
+        Ok(Some(Self {
+            original,
+            order: plan.order.clone(),
+            members,
+        }))
+    }
+
+    fn tip(&self) -> Oid {
+        self.original[self.original.len() - 1]
+    }
+
+    /// Cherry-picks the run in its new order onto the (possibly rewritten,
+    /// dropped or pruned) parent of its oldest commit and returns the new end
+    /// of the run. Commits that keep their position and edits are reused as
+    /// they are.
+    #[allow(clippy::too_many_arguments)]
+    fn replay(
+        &self,
+        repo: &Repository,
+        plan: &RewritePlan,
+        trees: &mut TreeRewriter,
+        commit_map: &mut HashMap<Oid, Oid>,
+        successor: &HashMap<Oid, Oid>,
+        lost_signatures: &mut Vec<Oid>,
+        hash_refs: &mut Vec<HashRefUpdate>,
+    ) -> Result<Oid> {
+        let base = repo.find_commit(self.original[0])?.parent_id(0)?;
+        let mut onto = new_location(base, commit_map, successor);
+
+        for oid in &self.order {
+            let commit = repo.find_commit(*oid)?;
+            let in_place = commit.parent_id(0)? == onto;
+            let tree = if in_place {
+                trees.rewrite(repo, commit.tree_id())?
+            } else {
+                cherry_pick_tree(repo, trees, &commit, onto)?
+            };
+            let (message, substituted) = rewritten_message(repo, plan, &commit, commit_map);
+            if in_place
+                && !plan.edits.contains_key(oid)
+                && substituted.is_empty()
+                && tree == commit.tree_id()
+            {
+                onto = *oid;
+                continue;
+            }
+
+            let new_oid = recreate_commit(repo, plan, &commit, &message, tree, &[onto])?;
+            hash_refs.extend(substituted);
+            if plan.signer.is_none() && signing::is_signed(&commit) {
+                lost_signatures.push(*oid);
+            }
+            commit_map.insert(*oid, new_oid);
+            onto = new_oid;
+        }
+
+        Ok(onto)
+    }
+}
+
+/// A commit whose changes do not apply cleanly on its new parent.
+#[derive(Debug)]
+pub struct ReplayConflict {
+    pub commit: Oid,
+    pub summary: String,
+    pub paths: Vec<String>,
+}
+
+impl std::fmt::Display for ReplayConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Commit {} \"{}\" no longer applies cleanly; conflicts in: {}",
+            short(self.commit),
+            self.summary,
+            self.paths.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ReplayConflict {}
+
+/// Applies the changes `commit` made relative to its first parent on top of
+/// `onto`, entirely in memory, and returns the resulting tree. Both sides of
+/// the change are filtered first, so purged paths never come back.
+pub fn cherry_pick_tree(
+    repo: &Repository,
+    trees: &mut TreeRewriter,
+    commit: &git2::Commit,
+    onto: Oid,
+) -> Result<Oid> {
+    apply_change(
+        repo,
+        trees,
+        commit,
+        commit.parent(0)?.tree_id(),
+        commit.tree_id(),
+        onto,
+    )
+}
+
+/// Applies the change from tree `base` to tree `changed` on top of commit
+/// `onto` with a 3-way merge; conflicts are reported against `commit`.
+fn apply_change(
+    repo: &Repository,
+    trees: &mut TreeRewriter,
+    commit: &git2::Commit,
+    base: Oid,
+    changed: Oid,
+    onto: Oid,
+) -> Result<Oid> {
+    let ancestor = repo.find_tree(trees.rewrite(repo, base)?)?;
+    let ours = repo.find_commit(onto)?.tree()?;
+    let theirs = repo.find_tree(trees.rewrite(repo, changed)?)?;
+    let mut index = repo.merge_trees(&ancestor, &ours, &theirs, None)?;
+
+    if index.has_conflicts() {
+        let mut paths = Vec::new();
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+                paths.push(String::from_utf8_lossy(&entry.path).to_string());
+            }
+        }
+        paths.sort();
+        paths.dedup();
+        return Err(ReplayConflict {
+            commit: commit.id(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            paths,
+        }
+        .into());
+    }
+
+    Ok(index.write_tree_to(repo)?)
+}
+
+fn short(oid: Oid) -> String {
+    oid.to_string().chars().take(8).collect()
+}
+
+/// Git stores messages with a single trailing newline.
+pub fn normalize_message(message: &str) -> String {
+    format!("{}\n", message.trim_end())
+}
+
+/// Applies `edit` to `original`, keeping any field the edit leaves unset
+/// (including the original UTC offset).
+fn edit_signature(original: &Signature, edit: &SignatureEdit) -> Result<Signature<'static>> {
+    let name = match &edit.name {
+        Some(name) => name.clone(),
+        None => String::from_utf8_lossy(original.name_bytes()).to_string(),
+    };
+    let email = match &edit.email {
+        Some(email) => email.clone(),
+        None => String::from_utf8_lossy(original.email_bytes()).to_string(),
+    };
+    let when = original.when();
+    let time = edit.apply_time(CommitTime::new(when.seconds(), when.offset_minutes()))?;
+    Ok(Signature::new(
+        &name,
+        &email,
+        &git2::Time::new(time.seconds, time.offset_minutes),
+    )?)
+}
+
+fn resolve_tips(repo: &Repository, refs: &[String]) -> Result<Vec<(String, Oid)>> {
+    let mut tips = Vec::new();
+    for name in refs {
+        let reference = repo
+            .find_reference(name)
+            .with_context(|| format!("Ref {} not found", name))?;
+        // HEAD is only moved directly when it's detached
+        if reference.symbolic_target().is_some() {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            tips.push((name.clone(), commit.id()));
+        }
+    }
+    Ok(tips)
+}
+
+/// Parents of the target commits, minus any that descend from another
+/// target. Hiding these keeps the walk limited to the part of history that
+/// actually needs rewriting.
+fn boundary_commits(repo: &Repository, targets: &[Oid]) -> Result<Vec<Oid>> {
+    let mut boundary = Vec::new();
+    for target in targets {
+        let commit = repo.find_commit(*target)?;
+        for parent in commit.parent_ids() {
+            let mut needs_rewrite = false;
+            for other in targets {
+                if parent == *other || repo.graph_descendant_of(parent, *other)? {
+                    needs_rewrite = true;
+                    break;
+                }
+            }
+            if !needs_rewrite {
+                boundary.push(parent);
+            }
+        }
+    }
+    Ok(boundary)
+}
+
+/// Maps every commit folded into a squash to the last commit of its group,
+/// checking that each group is a linear run of single-parent commits.
+fn folded_commits(
+    repo: &Repository,
+    edits: &HashMap<Oid, CommitEdit>,
+) -> Result<HashMap<Oid, Oid>> {
+    let mut folded = HashMap::new();
+    for (head, edit) in edits {
+        let Some(last) = edit.squash.last() else {
+            continue;
+        };
+
+        let mut previous = *head;
+        for member in &edit.squash {
+            let commit = repo.find_commit(*member)?;
+            if commit.parent_count() != 1 || commit.parent_id(0)? != previous {
+                anyhow::bail!(
+                    "Commits to squash must be adjacent: {} is not a child of {}",
+                    member,
+                    previous
+                );
+            }
+            if edit.drop || edits.contains_key(member) || folded.insert(*member, *last).is_some() {
+                anyhow::bail!("Commit {} is squashed and edited at the same time", member);
+            }
+            previous = *member;
+        }
+    }
+    Ok(folded)
+}
+
+/// Moves the refs, keeping their old positions both in `refs/original/`
+/// (replaced on every run) and in a new undo-history backup, whose id is
+/// returned. An open operation journal records the moves first.
+fn update_refs(repo: &Repository, updates: &[RefUpdate]) -> Result<Option<String>> {
+    for reference in repo.references_glob(&format!("{}*", BACKUP_NAMESPACE))? {
+        reference?.delete()?;
+    }
+    let backup = if updates.is_empty() {
+        None
+    } else {
+        let old: Vec<(String, Oid)> = updates
+            .iter()
+            .map(|update| (update.name.clone(), update.old_oid))
+            .collect();
+        Some(backups::save_backup(repo, &old)?)
+    };
+    journal::record_ref_updates(
+        repo,
+        &updates
+            .iter()
+            .map(|update| (update.name.clone(), update.old_oid, update.new_oid))
+            .collect::<Vec<_>>(),
+        backup.as_deref(),
+    )?;
+
+    sync_worktree(
+        repo,
+        &updates
+            .iter()
+            .map(|update| (update.name.clone(), update.new_oid))
+            .collect::<Vec<_>>(),
+    )?;
+    for update in updates {
+        repo.reference(
+            &format!("{}{}", BACKUP_NAMESPACE, update.name),
+            update.old_oid,
+            true,
+            "commit-rewriter: backup",
+        )?;
+
+        if update.name == "HEAD" {
+            repo.set_head_detached(update.new_oid)?;
+        } else {
+            repo.reference(
+                &update.name,
+                update.new_oid,
+                true,
+                "commit-rewriter: rewrite",
+            )?;
+        }
+    }
+
+    journal::set_phase(repo, JournalPhase::AwaitingConfirm)?;
+    Ok(backup)
+}
+
+/// Brings the index and work tree along when one of `moves` (ref name and
+/// new target) replaces the checked-out commit, like filter-branch's final
+/// `read-tree`. Must run before the refs move, so HEAD still names the
+/// commit the files came from; files with local changes are left alone.
+pub fn sync_worktree(repo: &Repository, moves: &[(String, Oid)]) -> Result<()> {
+    if repo.is_bare() {
+        return Ok(());
+    }
+    let Ok(head) = repo.head() else {
+        return Ok(());
+    };
+    let Some(head_name) = head.name() else {
+        return Ok(());
+    };
+    let Some((_, target)) = moves.iter().find(|(name, _)| name == head_name) else {
+        return Ok(());
+    };
+    let old_tree = head.peel_to_tree()?;
+    let new_tree = repo.find_object(*target, None)?.peel_to_tree()?;
+    if old_tree.id() == new_tree.id() {
+        return Ok(());
+    }
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe().allow_conflicts(true);
+    repo.checkout_tree(new_tree.as_object(), Some(&mut checkout))
+        .context("Failed to update the work tree")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+    use crate::models::TimeEdit;
+    use std::path::Path;
+
+    #[test]
+    fn test_rewrite_only_touches_target_and_descendants() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        let second = repo.commit("second", &[("a.txt", "2")]);
+        let third = repo.commit("third", &[("a.txt", "3")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            second,
+            CommitEdit {
+                message: Some("second, reworded".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        assert!(!outcome.commit_map.contains_key(&first));
+        assert_eq!(outcome.commit_map.len(), 2);
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id(), outcome.commit_map[&third]);
+        assert_eq!(head.message(), Some("third"));
+
+        let new_second = head.parent(0).unwrap();
+        assert_eq!(new_second.message(), Some("second, reworded\n"));
+        assert_eq!(new_second.parent_id(0).unwrap(), first);
+        assert_eq!(
+            new_second.tree_id(),
+            repo.repo.find_commit(second).unwrap().tree_id()
+        );
+
+        let backup = repo
+            .repo
+            .find_reference("refs/original/refs/heads/main")
+            .unwrap();
+        assert_eq!(backup.target(), Some(third));
+    }
+
+    #[test]
+    fn test_rewrite_identity_keeps_dates_and_message() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            first,
+            CommitEdit {
+                author: SignatureEdit {
+                    email: Some("test@company.com".to_string()),
+                    ..Default::default()
+                },
+                committer: SignatureEdit {
+                    name: Some("Release Bot".to_string()),
+                    time: Some(TimeEdit::Set(CommitTime::new(1_600_000_000, -300))),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        rewrite_history(&repo.repo, &plan).unwrap();
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        let original = repo.repo.find_commit(first).unwrap();
+        assert_eq!(head.message(), Some("first"));
+        assert_eq!(head.author().name(), Some("Test User"));
+        assert_eq!(head.author().email(), Some("test@company.com"));
+        assert_eq!(head.committer().name(), Some("Release Bot"));
+        assert_eq!(head.committer().email(), Some("test@example.com"));
+        assert_eq!(head.author().when(), original.author().when());
+        assert_eq!(head.committer().when().seconds(), 1_600_000_000);
+        assert_eq!(head.committer().when().offset_minutes(), -300);
+    }
+
+    #[test]
+    #[ignore = "needs ssh-keygen; run with --ignored"]
+    fn test_rewrite_resigns_or_reports_lost_signatures() {
+        let repo = TestRepo::new();
+        repo.commit("first", &[("a.txt", "1")]);
+        let second = repo.commit("second", &[("a.txt", "2")]);
+
+        let key = repo.path().join("signing_key");
+        let keygen = std::process::Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&key)
+            .status()
+            .expect("ssh-keygen not available");
+        assert!(keygen.success());
+        let mut config = repo.repo.config().unwrap();
+        config.set_str("gpg.format", "ssh").unwrap();
+        config
+            .set_str("user.signingkey", key.to_str().unwrap())
+            .unwrap();
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            signer: Some(Signer::from_config(&repo.repo).unwrap()),
+            ..Default::default()
+        };
+        plan.edits.insert(
+            second,
+            CommitEdit {
+                message: Some("second, signed".to_string()),
+                ..Default::default()
+            },
+        );
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+        assert!(outcome.lost_signatures.is_empty());
+
+        let signed = outcome.commit_map[&second];
+        let header = repo
+            .repo
+            .find_commit(signed)
+            .unwrap()
+            .header_field_bytes("gpgsig")
+            .unwrap();
+        assert!(header.as_str().unwrap().contains("BEGIN SSH SIGNATURE"));
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            signed,
+            CommitEdit {
+                message: Some("second, unsigned".to_string()),
+                ..Default::default()
+            },
+        );
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+        assert_eq!(outcome.lost_signatures, vec![signed]);
+    }
+
+    #[test]
+    fn test_squash_takes_newest_tree_and_drops_folded_commits() {
+        let repo = TestRepo::new();
+        let base = repo.commit("base", &[("a.txt", "0")]);
+        let feature = repo.commit("Add feature", &[("a.txt", "1")]);
+        let fixup = repo.commit("fixup! Add feature", &[("a.txt", "2")]);
+        let follow_up = repo.commit("Tweak feature", &[("b.txt", "1")]);
+        let tip = repo.commit("tip", &[("c.txt", "1")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            feature,
+            CommitEdit {
+                message: Some("Add feature\n\nTweak feature".to_string()),
+                squash: vec![fixup, follow_up],
+                ..Default::default()
+            },
+        );
+
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+        let squashed = outcome.commit_map[&feature];
+        assert_eq!(outcome.commit_map[&fixup], squashed);
+        assert_eq!(outcome.commit_map[&follow_up], squashed);
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id(), outcome.commit_map[&tip]);
+        assert_eq!(head.parent_id(0).unwrap(), squashed);
+
+        let squashed = repo.repo.find_commit(squashed).unwrap();
+        assert_eq!(squashed.message(), Some("Add feature\n\nTweak feature\n"));
+        assert_eq!(squashed.parent_ids().collect::<Vec<_>>(), vec![base]);
+        assert_eq!(
+            squashed.tree_id(),
+            repo.repo.find_commit(follow_up).unwrap().tree_id()
+        );
+    }
+
+    #[test]
+    fn test_squash_after_dropped_commit_keeps_it_dropped() {
+        let repo = TestRepo::new();
+        let base = repo.commit("base", &[("a.txt", "0")]);
+        let debug = repo.commit("debug logging", &[("debug.txt", "on")]);
+        let feature = repo.commit("Add feature", &[("a.txt", "1")]);
+        let fixup = repo.commit("fixup! Add feature", &[("a.txt", "2")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            debug,
+            CommitEdit {
+                drop: true,
+                ..Default::default()
+            },
+        );
+        plan.edits.insert(
+            feature,
+            CommitEdit {
+                squash: vec![fixup],
+                ..Default::default()
+            },
+        );
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        let squashed = repo.repo.find_commit(outcome.commit_map[&feature]).unwrap();
+        assert_eq!(squashed.parent_id(0).unwrap(), base);
+        let tree = squashed.tree().unwrap();
+        assert!(tree.get_path(Path::new("debug.txt")).is_err());
+        let blob = tree.get_path(Path::new("a.txt")).unwrap().id();
+        assert_eq!(repo.repo.find_blob(blob).unwrap().content(), b"2");
+    }
+
+    #[test]
+    fn test_squash_rejects_non_adjacent_or_shared_commits() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        let second = repo.commit("second", &[("a.txt", "2")]);
+        repo.branch("side");
+        repo.commit("side work", &[("s.txt", "1")]);
+        repo.checkout("main");
+        let third = repo.commit("third", &[("a.txt", "3")]);
+
+        let squash = |members: Vec<Oid>| {
+            let mut plan = RewritePlan {
+                refs: vec!["refs/heads/main".to_string(), "refs/heads/side".to_string()],
+                ..Default::default()
+            };
+            plan.edits.insert(
+                first,
+                CommitEdit {
+                    squash: members,
+                    ..Default::default()
+                },
+            );
+            plan
+        };
+
+        let err = rewrite_history(&repo.repo, &squash(vec![third])).unwrap_err();
+        assert!(err.to_string().contains("must be adjacent"), "{}", err);
+
+        // side still builds on second, so second can't disappear
+        let err = rewrite_history(&repo.repo, &squash(vec![second, third])).unwrap_err();
+        assert!(err.to_string().contains("also builds on it"), "{}", err);
+    }
+
+    #[test]
+    fn test_reorder_replays_commits_in_new_order() {
+        let repo = TestRepo::new();
+        let base = repo.commit("base", &[("a.txt", "0")]);
+        let a = repo.commit("add b", &[("b.txt", "1")]);
+        let b = repo.commit("add c", &[("c.txt", "1")]);
+        let c = repo.commit("add d", &[("d.txt", "1")]);
+
+        let plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            order: vec![c, a, b],
+            ..Default::default()
+        };
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id(), outcome.commit_map[&b]);
+        assert_eq!(head.tree_id(), repo.repo.find_commit(c).unwrap().tree_id());
+
+        let summaries: Vec<String> = {
+            let mut walk = repo.repo.revwalk().unwrap();
+            walk.push_head().unwrap();
+            walk.map(|oid| {
+                let commit = repo.repo.find_commit(oid.unwrap()).unwrap();
+                commit.summary().unwrap().to_string()
+            })
+            .collect()
+        };
+        assert_eq!(summaries, vec!["add c", "add b", "add d", "base"]);
+
+        let moved_first = repo.repo.find_commit(outcome.commit_map[&c]).unwrap();
+        assert_eq!(moved_first.parent_id(0).unwrap(), base);
+        assert!(moved_first
+            .tree()
+            .unwrap()
+            .get_path(Path::new("b.txt"))
+            .is_err());
+
+        let recorded = commit_map::load(&repo.repo, outcome.backup.as_deref().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(recorded.len(), 3);
+        assert!(recorded
+            .iter()
+            .all(|(_, new)| *new != Oid::zero().to_string()));
+    }
+
+    #[test]
+    fn test_reorder_after_dropped_base_keeps_it_dropped() {
+        let repo = TestRepo::new();
+        let a = repo.commit("a", &[("a.txt", "1")]);
+        let b = repo.commit("b", &[("b.txt", "1")]);
+        let c = repo.commit("c", &[("c.txt", "1")]);
+        let d = repo.commit("d", &[("d.txt", "1")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            order: vec![d, c],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            b,
+            CommitEdit {
+                drop: true,
+                ..Default::default()
+            },
+        );
+        rewrite_history(&repo.repo, &plan).unwrap();
+
+        let mut walk = repo.repo.revwalk().unwrap();
+        walk.push_head().unwrap();
+        let history: Vec<Oid> = walk.map(|oid| oid.unwrap()).collect();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2], a);
+        let summaries: Vec<String> = history
+            .iter()
+            .map(|oid| {
+                let commit = repo.repo.find_commit(*oid).unwrap();
+                commit.summary().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(summaries, vec!["c", "d", "a"]);
+        let head = repo.repo.head().unwrap().peel_to_tree().unwrap();
+        assert!(head.get_path(Path::new("b.txt")).is_err());
+    }
+
+    #[test]
+    fn test_reorder_in_place_is_not_recorded_as_removed() {
+        let repo = TestRepo::new();
+        repo.commit("base", &[("a.txt", "0")]);
+        let a = repo.commit("add b", &[("b.txt", "1")]);
+        let b = repo.commit("add c", &[("c.txt", "1")]);
+        let tip = repo.commit("tip", &[("d.txt", "1")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            order: vec![a, b],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            tip,
+            CommitEdit {
+                message: Some("tip, reworded".to_string()),
+                ..Default::default()
+            },
+        );
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        let recorded = commit_map::load(&repo.repo, outcome.backup.as_deref().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            recorded,
+            vec![(tip.to_string(), outcome.commit_map[&tip].to_string())]
+        );
+    }
+
+    #[test]
+    fn test_reorder_refuses_conflicts_and_names_paths() {
+        let repo = TestRepo::new();
+        repo.commit("base", &[("a.txt", "1")]);
+        let first = repo.commit("first", &[("a.txt", "2")]);
+        let second = repo.commit("second", &[("a.txt", "3")]);
+
+        let plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            order: vec![second, first],
+            ..Default::default()
+        };
+        let err = rewrite_history(&repo.repo, &plan).unwrap_err();
+        let conflict = err.downcast_ref::<ReplayConflict>().unwrap();
+        assert_eq!(conflict.commit, second);
+        assert_eq!(conflict.paths, vec!["a.txt".to_string()]);
+
+        // nothing moved
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id(), second);
+        assert!(repo
+            .repo
+            .find_reference("refs/original/refs/heads/main")
+            .is_err());
+    }
+
+    #[test]
+    fn test_hash_references_follow_rewritten_commits() {
+        let repo = TestRepo::new();
+        let first = repo.commit("first", &[("a.txt", "1")]);
+        let second = repo.commit("second", &[("a.txt", "2")]);
+        let message = format!(
+            "Revert \"second\"\n\nThis reverts commit {}.\nFollows up {}, not deadbeefcafe.\n{}",
+            second,
+            &first.to_string()[..7],
+            "Reported 20231015."
+        );
+        let third = repo.commit(&message, &[("a.txt", "1")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            update_hash_refs: true,
+            ..Default::default()
+        };
+        plan.edits.insert(
+            first,
+            CommitEdit {
+                message: Some("first, reworded".to_string()),
+                ..Default::default()
+            },
+        );
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        let new_first = outcome.commit_map[&first].to_string();
+        let new_second = outcome.commit_map[&second].to_string();
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(
+            head.message().unwrap(),
+            format!(
+                "Revert \"second\"\n\nThis reverts commit {}.\nFollows up {}, not deadbeefcafe.\n{}",
+                new_second,
+                &new_first[..7],
+                "Reported 20231015."
+            )
+        );
+        assert_eq!(outcome.hash_refs.len(), 2);
+        assert!(outcome
+            .hash_refs
+            .iter()
+            .all(|update| update.commit == third));
+        assert_eq!(outcome.hash_refs[1].new, new_first[..7]);
+    }
+
+    #[test]
+    fn test_abbreviation_grows_until_unambiguous() {
+        let repo = TestRepo::new();
+        let commit = repo.commit("commit", &[("a.txt", "1")]);
+        let hex = commit.to_string();
+        assert_eq!(abbreviate(&repo.repo, commit, 4), hex[..4]);
+
+        // a blob sharing the first four hex digits makes that prefix ambiguous
+        let data = (0u32..)
+            .map(|n| n.to_string())
+            .find(|data| {
+                git2::Oid::hash_object(git2::ObjectType::Blob, data.as_bytes())
+                    .unwrap()
+                    .to_string()[..4]
+                    == hex[..4]
+            })
+            .unwrap();
+        repo.repo.blob(data.as_bytes()).unwrap();
+        let longer = abbreviate(&repo.repo, commit, 4);
+        assert!(longer.len() > 4 && hex.starts_with(&longer));
+    }
+
+    #[test]
+    fn test_drop_replays_descendants_onto_parent() {
+        let repo = TestRepo::new();
+        let base = repo.commit("base", &[("a.txt", "0")]);
+        let debug = repo.commit("debug logging", &[("debug.txt", "on")]);
+        let feature = repo.commit("feature", &[("a.txt", "1")]);
+        let tip = repo.commit("tip", &[("b.txt", "1")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            debug,
+            CommitEdit {
+                drop: true,
+                ..Default::default()
+            },
+        );
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        assert!(!outcome.commit_map.contains_key(&debug));
+        assert_eq!(outcome.commit_map.len(), 2);
+        let recorded = commit_map::load(&repo.repo, outcome.backup.as_deref().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(recorded.len(), 3);
+        assert!(recorded.contains(&(debug.to_string(), Oid::zero().to_string())));
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id(), outcome.commit_map[&tip]);
+        let tree = head.tree().unwrap();
+        assert!(tree.get_path(Path::new("debug.txt")).is_err());
+        assert!(tree.get_path(Path::new("a.txt")).is_ok());
+        assert!(tree.get_path(Path::new("b.txt")).is_ok());
+
+        let new_feature = head.parent(0).unwrap();
+        assert_eq!(new_feature.id(), outcome.commit_map[&feature]);
+        assert_eq!(new_feature.parent_id(0).unwrap(), base);
+
+        // dropping the tip just moves the branch back
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            head.id(),
+            CommitEdit {
+                drop: true,
+                ..Default::default()
+            },
+        );
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+        assert!(outcome.commit_map.is_empty());
+        assert_eq!(outcome.ref_updates[0].new_oid, new_feature.id());
+    }
+
+    #[test]
+    fn test_drop_refuses_conflicting_descendant() {
+        let repo = TestRepo::new();
+        repo.commit("base", &[("a.txt", "1")]);
+        let dropped = repo.commit("change a", &[("a.txt", "2")]);
+        let later = repo.commit("change a again", &[("a.txt", "3")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            dropped,
+            CommitEdit {
+                drop: true,
+                ..Default::default()
+            },
+        );
+        let err = rewrite_history(&repo.repo, &plan).unwrap_err();
+        let conflict = err.downcast_ref::<ReplayConflict>().unwrap();
+        assert_eq!(conflict.commit, later);
+        assert_eq!(conflict.paths, vec!["a.txt".to_string()]);
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id(), later);
+    }
+
+    #[test]
+    fn test_rewrite_rejects_unreachable_commit() {
+        let repo = TestRepo::new();
+        repo.commit("first", &[("a.txt", "1")]);
+        repo.branch("side");
+        let side = repo.commit("side", &[("b.txt", "1")]);
+        repo.checkout("main");
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            ..Default::default()
+        };
+        plan.edits.insert(side, CommitEdit::default());
+
+        assert!(rewrite_history(&repo.repo, &plan).is_err());
+    }
+
+    #[test]
+    fn test_purge_removes_paths_and_prunes_emptied_commits() {
+        let repo = TestRepo::new();
+        let base = repo.commit("base", &[("a.txt", "0"), ("keys/prod.pem", "1")]);
+        let rotate = repo.commit("rotate key", &[("keys/prod.pem", "2")]);
+        let marker = repo.commit("already empty", &[]);
+        let feature = repo.commit("feature", &[("a.txt", "1"), ("keys/prod.pem", "3")]);
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            tree_filter: TreeFilter {
+                purge: vec!["*.pem".to_string()],
+                prune_empty: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        assert_eq!(outcome.pruned, vec![rotate]);
+        assert!(!outcome.commit_map.contains_key(&rotate));
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id(), outcome.commit_map[&feature]);
+        assert!(head.tree().unwrap().get_path(Path::new("keys")).is_err());
+        assert!(head.tree().unwrap().get_path(Path::new("a.txt")).is_ok());
+        // the checked-out files follow, leaving a clean status
+        assert!(!repo.path().join("keys/prod.pem").exists());
+        assert!(repo.repo.statuses(None).unwrap().is_empty());
+
+        // commits that were empty to begin with are kept
+        let new_marker = head.parent(0).unwrap();
+        assert_eq!(new_marker.id(), outcome.commit_map[&marker]);
+        let new_base = new_marker.parent(0).unwrap();
+        assert_eq!(new_base.id(), outcome.commit_map[&base]);
+        assert_eq!(new_base.parent_count(), 0);
+
+        // without pruning the emptied commit stays, and a clean tree is a no-op
+        let repo = TestRepo::new();
+        repo.commit("base", &[("a.txt", "0"), ("keys/prod.pem", "1")]);
+        let rotate = repo.commit("rotate key", &[("keys/prod.pem", "2")]);
+        plan.tree_filter.prune_empty = false;
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+        assert!(outcome.pruned.is_empty());
+        assert_eq!(outcome.commit_map.len(), 2);
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id(), outcome.commit_map[&rotate]);
+        assert_eq!(head.tree_id(), head.parent(0).unwrap().tree_id());
+
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+        assert!(outcome.commit_map.is_empty());
+        assert!(outcome.ref_updates.is_empty());
+    }
+
+    #[test]
+    fn test_strip_blobs_by_size_and_id() {
+        let repo = TestRepo::new();
+        let big = "x".repeat(4096);
+        let base = repo.commit("base", &[("a.txt", "0"), ("video.bin", &big)]);
+        repo.commit("docs", &[("README", "hi"), ("logo.png", "png")]);
+
+        let logo = repo
+            .repo
+            .head()
+            .unwrap()
+            .peel_to_tree()
+            .unwrap()
+            .get_path(Path::new("logo.png"))
+            .unwrap()
+            .id();
+        let plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            tree_filter: TreeFilter {
+                strip_blobs: HashSet::from([logo]),
+                max_blob_size: Some(1024),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        assert_eq!(outcome.stripped_blobs, 2);
+        assert_eq!(outcome.stripped_bytes, 4096 + 3);
+        assert!(outcome.commit_map.contains_key(&base));
+
+        let tree = repo.repo.head().unwrap().peel_to_tree().unwrap();
+        assert!(tree.get_path(Path::new("video.bin")).is_err());
+        assert!(tree.get_path(Path::new("logo.png")).is_err());
+        assert!(tree.get_path(Path::new("a.txt")).is_ok());
+        assert!(tree.get_path(Path::new("README")).is_ok());
+    }
+
+    #[test]
+    fn test_replace_text_keeps_untouched_commits() {
+        let repo = TestRepo::new();
+        let base = repo.commit("base", &[("README", "hello")]);
+        let leak = repo.commit("config", &[("app.cfg", "user=admin\npassword=hunter2\n")]);
+        let tip = repo.commit("more", &[("README", "hello world")]);
+
+        let plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            tree_filter: TreeFilter {
+                replace_text: vec!["hunter2".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        assert_eq!(outcome.replaced_blobs, 1);
+        assert!(!outcome.commit_map.contains_key(&base));
+        assert!(outcome.commit_map.contains_key(&leak));
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id(), outcome.commit_map[&tip]);
+        assert_eq!(head.parent(0).unwrap().parent_id(0).unwrap(), base);
+        let blob = head
+            .tree()
+            .unwrap()
+            .get_path(Path::new("app.cfg"))
+            .unwrap()
+            .to_object(&repo.repo)
+            .unwrap()
+            .peel_to_blob()
+            .unwrap();
+        assert_eq!(blob.content(), b"user=admin\npassword=***REMOVED***\n");
+    }
+
+    #[test]
+    fn test_tags_follow_rewritten_commits() {
+        let repo = TestRepo::new();
+        let base = repo.commit("base", &[("a.txt", "0")]);
+        let release = repo.commit("relase", &[("a.txt", "1")]);
+        repo.commit("tip", &[("a.txt", "2")]);
+
+        let sig = TestRepo::signature();
+        let base_commit = repo.repo.find_commit(base).unwrap();
+        let release_commit = repo.repo.find_commit(release).unwrap();
+        repo.repo
+            .tag("v0.9", base_commit.as_object(), &sig, "Beta\n", false)
+            .unwrap();
+        let old_tag = repo
+            .repo
+            .tag("v1.0", release_commit.as_object(), &sig, "Release\n", false)
+            .unwrap();
+
+        let mut plan = RewritePlan {
+            refs: vec!["refs/heads/main".to_string()],
+            rewrite_tags: true,
+            ..Default::default()
+        };
+        plan.edits.insert(
+            release,
+            CommitEdit {
+                message: Some("release".to_string()),
+                ..Default::default()
+            },
+        );
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        assert_eq!(outcome.tags.len(), 1);
+        let reference = repo.repo.find_reference("refs/tags/v1.0").unwrap();
+        let tag = reference.peel_to_tag().unwrap();
+        assert_eq!(tag.target_id(), outcome.commit_map[&release]);
+        assert_eq!(tag.message(), Some("Release\n"));
+        let backup = repo
+            .repo
+            .find_reference("refs/original/refs/tags/v1.0")
+            .unwrap();
+        assert_eq!(backup.target(), Some(old_tag));
+        let history = backups::list_backups(&repo.repo).unwrap();
+        assert_eq!(Some(&history[0].id), outcome.backup.as_ref());
+        assert!(history[0].refs.iter().any(|r| r.name == "refs/tags/v1.0"));
+
+        // tags on untouched commits stay put
+        let beta = repo.repo.find_reference("refs/tags/v0.9").unwrap();
+        assert_eq!(beta.peel_to_commit().unwrap().id(), base);
+    }
+
+    #[test]
+    fn test_notes_follow_rewritten_commits() {
+        let repo = TestRepo::new();
+        let target = repo.commit("tpyo", &[("a.txt", "1")]);
+        let child = repo.commit("child", &[("a.txt", "2")]);
+        let sig = TestRepo::signature();
+        let notes_ref = "refs/notes/commits".to_string();
+        repo.repo
+            .note(&sig, &sig, Some(&notes_ref), child, "ci: passed\n", false)
+            .unwrap();
+        let old_tip = repo.repo.refname_to_id(&notes_ref).unwrap();
+
+        // an all-branches rewrite leaves the notes ref to the note copying
+        let refs = collect_rewrite_refs(&repo.repo, true, None).unwrap();
+        assert_eq!(refs, vec!["refs/heads/main"]);
+        assert!(list_refs(&repo.repo)
+            .unwrap()
+            .iter()
+            .all(|entry| entry.kind != RefKind::Notes));
+
+        let mut plan = RewritePlan {
+            refs,
+            notes_refs: vec![notes_ref.clone()],
+            ..Default::default()
+        };
+        plan.edits.insert(
+            target,
+            CommitEdit {
+                message: Some("typo".to_string()),
+                ..Default::default()
+            },
+        );
+        journal::begin(&repo.repo, "Reword 1 commit(s)").unwrap();
+        let outcome = rewrite_history(&repo.repo, &plan).unwrap();
+
+        assert_eq!(outcome.notes.len(), 1);
+        assert_eq!(outcome.notes[0].copied, 1);
+        assert_eq!(
+            repo.repo.refname_to_id(&notes_ref).unwrap(),
+            outcome.notes[0].new_oid
+        );
+        let note = repo
+            .repo
+            .find_note(Some(&notes_ref), outcome.commit_map[&child])
+            .unwrap();
+        assert_eq!(note.message(), Some("ci: passed\n"));
+        let backup = repo
+            .repo
+            .find_reference("refs/original/refs/notes/commits")
+            .unwrap();
+        assert_eq!(backup.target(), Some(old_tip));
+
+        // the notes ref went through the journal, so rolling back restores it
+        journal::recover(&repo.repo, false).unwrap();
+        assert_eq!(repo.repo.refname_to_id(&notes_ref).unwrap(), old_tip);
+        assert!(repo
+            .repo
+            .find_note(Some(&notes_ref), outcome.commit_map[&child])
+            .is_err());
+    }
+}
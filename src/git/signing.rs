@@ -0,0 +1,200 @@
+use anyhow::{Context, Result};
+use git2::Repository;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SigningFormat {
+    OpenPgp,
+    X509,
+    Ssh,
+}
+
+impl SigningFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SigningFormat::OpenPgp => "openpgp",
+            SigningFormat::X509 => "x509",
+            SigningFormat::Ssh => "ssh",
+        }
+    }
+}
+
+/// Signs commit buffers the way `git commit -S` does, using the key and
+/// program configured in `user.signingkey` / `gpg.format` / `gpg.*.program`.
+#[derive(Clone, Debug)]
+pub struct Signer {
+    pub format: SigningFormat,
+    pub key: String,
+    pub program: String,
+}
+
+impl Signer {
+    pub fn from_config(repo: &Repository) -> Result<Self> {
+        let config = repo.config()?.snapshot()?;
+
+        let format = match config.get_string("gpg.format").ok().as_deref() {
+            None | Some("openpgp") => SigningFormat::OpenPgp,
+            Some("x509") => SigningFormat::X509,
+            Some("ssh") => SigningFormat::Ssh,
+            Some(other) => anyhow::bail!("Unsupported gpg.format: {}", other),
+        };
+
+        let program = match format {
+            SigningFormat::OpenPgp => config
+                .get_string("gpg.openpgp.program")
+                .or_else(|_| config.get_string("gpg.program"))
+                .unwrap_or_else(|_| "gpg".to_string()),
+            SigningFormat::X509 => config
+                .get_string("gpg.x509.program")
+                .unwrap_or_else(|_| "gpgsm".to_string()),
+            SigningFormat::Ssh => config
+                .get_string("gpg.ssh.program")
+                .unwrap_or_else(|_| "ssh-keygen".to_string()),
+        };
+
+        let key = match config.get_string("user.signingkey") {
+            Ok(key) => key,
+            // gpg falls back to the committer identity, ssh has no such default
+            Err(_) if format != SigningFormat::Ssh => {
+                let name = config.get_string("user.name").unwrap_or_default();
+                let email = config.get_string("user.email").unwrap_or_default();
+                if email.is_empty() {
+                    anyhow::bail!("Neither user.signingkey nor user.email is configured");
+                }
+                format!("{} <{}>", name, email)
+            }
+            Err(_) => anyhow::bail!("user.signingkey is not set (required for gpg.format=ssh)"),
+        };
+
+        Ok(Self {
+            format,
+            key,
+            program,
+        })
+    }
+
+    /// Human-readable description for the UI, e.g. `ssh: ~/.ssh/id_ed25519.pub`.
+    pub fn describe(&self) -> String {
+        format!("{}: {}", self.format.name(), self.key)
+    }
+
+    /// Returns the detached signature for `payload`.
+    pub fn sign(&self, payload: &str) -> Result<String> {
+        match self.format {
+            SigningFormat::OpenPgp | SigningFormat::X509 => run_signer(
+                &self.program,
+                &["--status-fd=2", "-bsau", &self.key],
+                payload,
+            ),
+            SigningFormat::Ssh => self.sign_ssh(payload),
+        }
+    }
+
+    fn sign_ssh(&self, payload: &str) -> Result<String> {
+        let literal_key = self.key.strip_prefix("key::").or_else(|| {
+            let is_literal = ["ssh-", "ecdsa-", "sk-"]
+                .iter()
+                .any(|prefix| self.key.starts_with(prefix));
+            is_literal.then_some(self.key.as_str())
+        });
+
+        match literal_key {
+            // ssh-keygen needs a file, and signs through the agent with -U
+            Some(public_key) => {
+                let key_file = temp_key_path();
+                std::fs::write(&key_file, public_key)?;
+                let key_path = key_file.to_string_lossy().to_string();
+                let result = run_signer(
+                    &self.program,
+                    &["-Y", "sign", "-n", "git", "-f", &key_path, "-U"],
+                    payload,
+                );
+                std::fs::remove_file(&key_file).ok();
+                result
+            }
+            None => {
+                let key_path = expand_home(&self.key);
+                run_signer(
+                    &self.program,
+                    &["-Y", "sign", "-n", "git", "-f", &key_path],
+                    payload,
+                )
+            }
+        }
+    }
+}
+
+fn run_signer(program: &str, args: &[&str], payload: &str) -> Result<String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(payload.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    let signature = String::from_utf8_lossy(&output.stdout).to_string();
+
+    if !output.status.success() || signature.trim().is_empty() {
+        anyhow::bail!(
+            "{} failed to sign: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(signature)
+}
+
+fn temp_key_path() -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    std::env::temp_dir().join(format!(
+        "commit-rewriter-{}-{}.pub",
+        std::process::id(),
+        nanos
+    ))
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Whether the commit carries a `gpgsig` (or `gpgsig-sha256`) header.
+pub fn is_signed(commit: &git2::Commit) -> bool {
+    commit.header_field_bytes("gpgsig").is_ok()
+        || commit.header_field_bytes("gpgsig-sha256").is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+
+    #[test]
+    fn test_from_config_reads_ssh_settings() {
+        let repo = TestRepo::new();
+        let mut config = repo.repo.config().unwrap();
+        config.set_str("gpg.format", "ssh").unwrap();
+        config
+            .set_str("user.signingkey", "~/.ssh/id_ed25519.pub")
+            .unwrap();
+
+        let signer = Signer::from_config(&repo.repo).unwrap();
+        assert_eq!(signer.format, SigningFormat::Ssh);
+        assert_eq!(signer.program, "ssh-keygen");
+        assert_eq!(signer.describe(), "ssh: ~/.ssh/id_ed25519.pub");
+    }
+}
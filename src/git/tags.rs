@@ -0,0 +1,209 @@
+use crate::git::signing::Signer;
+use anyhow::{Context, Result};
+use git2::{ObjectType, Oid, Repository};
+
+/// Armor headers git appends to a tag message when signing it.
+const SIGNATURE_MARKERS: [&[u8]; 4] = [
+    b"-----BEGIN PGP SIGNATURE-----",
+    b"-----BEGIN PGP MESSAGE-----",
+    b"-----BEGIN SSH SIGNATURE-----",
+    b"-----BEGIN SIGNED MESSAGE-----",
+];
+
+/// A tag moved to the rewritten copy of the commit it pointed at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagUpdate {
+    pub name: String,
+    /// What the ref pointed at before and after: the commit for a
+    /// lightweight tag, the tag object for an annotated one.
+    pub old_oid: Oid,
+    pub new_oid: Oid,
+    pub old_commit: Oid,
+    pub new_commit: Oid,
+    pub annotated: bool,
+    /// The original tag carried a signature.
+    pub signed: bool,
+    /// The recreated tag was signed again.
+    pub resigned: bool,
+}
+
+/// Recreates the tags in `names` whose commit moved, per `locate`.
+/// Lightweight tags simply follow the commit; annotated tags get a new tag
+/// object with the same name, tagger and message. Signed tags are signed
+/// again with `signer`, or lose their signature without one. Tags on tags
+/// or on non-commits are left alone.
+pub fn rewrite_tags(
+    repo: &Repository,
+    names: &[String],
+    locate: impl Fn(Oid) -> Oid,
+    signer: Option<&Signer>,
+) -> Result<Vec<TagUpdate>> {
+    let mut updates = Vec::new();
+    for name in names {
+        let reference = repo
+            .find_reference(name)
+            .with_context(|| format!("Tag {} not found", name))?;
+        let Some(old_oid) = reference.target() else {
+            continue;
+        };
+
+        let object = repo.find_object(old_oid, None)?;
+        let (old_commit, annotated) = match object.kind() {
+            Some(ObjectType::Commit) => (old_oid, false),
+            Some(ObjectType::Tag) => {
+                let tag = repo.find_tag(old_oid)?;
+                if tag.target_type() != Some(ObjectType::Commit) {
+                    continue;
+                }
+                (tag.target_id(), true)
+            }
+            _ => continue,
+        };
+
+        let new_commit = locate(old_commit);
+        if new_commit == old_commit {
+            continue;
+        }
+
+        let (new_oid, signed, resigned) = if annotated {
+            recreate_tag(repo, old_oid, new_commit, signer)
+                .with_context(|| format!("Failed to recreate tag {}", name))?
+        } else {
+            (new_commit, false, false)
+        };
+
+        updates.push(TagUpdate {
+            name: name.clone(),
+            old_oid,
+            new_oid,
+            old_commit,
+            new_commit,
+            annotated,
+            signed,
+            resigned,
+        });
+    }
+    Ok(updates)
+}
+
+/// Writes a copy of the annotated tag `tag` pointing at `target`. The raw
+/// object is edited rather than rebuilt so the tagger line and any extra
+/// headers survive byte for byte. Returns the new tag's id, whether the
+/// original was signed and whether the copy is.
+fn recreate_tag(
+    repo: &Repository,
+    tag: Oid,
+    target: Oid,
+    signer: Option<&Signer>,
+) -> Result<(Oid, bool, bool)> {
+    let odb = repo.odb()?;
+    let raw = odb.read(tag)?;
+    let data = raw.data();
+
+    let split = data
+        .windows(2)
+        .position(|w| w == b"\n\n")
+        .context("Malformed tag object")?;
+    let (header, body) = (&data[..split + 1], &data[split + 2..]);
+    let (message, signed) = strip_signature(body);
+
+    let mut payload = Vec::with_capacity(data.len());
+    for line in header.split_inclusive(|b| *b == b'\n') {
+        if line.starts_with(b"object ") {
+            payload.extend_from_slice(format!("object {}\n", target).as_bytes());
+        } else {
+            payload.extend_from_slice(line);
+        }
+    }
+    payload.push(b'\n');
+    payload.extend_from_slice(message);
+
+    let mut resigned = false;
+    if let (true, Some(signer)) = (signed, signer) {
+        let text = std::str::from_utf8(&payload).context("Tag is not valid UTF-8")?;
+        let signature = signer.sign(text)?;
+        payload.extend_from_slice(signature.as_bytes());
+        resigned = true;
+    }
+
+    Ok((odb.write(ObjectType::Tag, &payload)?, signed, resigned))
+}
+
+/// Splits a trailing signature off a tag message.
+fn strip_signature(message: &[u8]) -> (&[u8], bool) {
+    let mut start = 0;
+    for line in message.split_inclusive(|b| *b == b'\n') {
+        if SIGNATURE_MARKERS
+            .iter()
+            .any(|marker| line.starts_with(marker))
+        {
+            return (&message[..start], true);
+        }
+        start += line.len();
+    }
+    (message, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+
+    #[test]
+    fn test_rewrite_tags_keeps_tagger_and_message() {
+        let repo = TestRepo::new();
+        let old = repo.commit("release", &[("a.txt", "1")]);
+        let new = repo.commit("rewritten release", &[("a.txt", "2")]);
+        let commit = repo.repo.find_commit(old).unwrap();
+        let tagger = git2::Signature::new(
+            "Release Bot",
+            "bot@example.com",
+            &git2::Time::new(1_600_000_000, 120),
+        )
+        .unwrap();
+        repo.repo
+            .tag("v1.0", commit.as_object(), &tagger, "Version 1.0\n", false)
+            .unwrap();
+        repo.repo
+            .tag_lightweight("nightly", commit.as_object(), false)
+            .unwrap();
+        let fake_signed =
+            "Signed 1.1\n-----BEGIN PGP SIGNATURE-----\nabc\n-----END PGP SIGNATURE-----\n";
+        repo.repo
+            .tag("v1.1", commit.as_object(), &tagger, fake_signed, false)
+            .unwrap();
+
+        let names: Vec<String> = ["refs/tags/nightly", "refs/tags/v1.0", "refs/tags/v1.1"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let updates = rewrite_tags(
+            &repo.repo,
+            &names,
+            |oid| if oid == old { new } else { oid },
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(updates.len(), 3);
+        assert!(!updates[0].annotated);
+        assert_eq!(updates[0].new_oid, new);
+
+        let v1 = repo.repo.find_tag(updates[1].new_oid).unwrap();
+        assert_eq!(v1.target_id(), new);
+        assert_eq!(v1.name(), Some("v1.0"));
+        assert_eq!(v1.message(), Some("Version 1.0\n"));
+        let new_tagger = v1.tagger().unwrap();
+        assert_eq!(new_tagger.name(), Some("Release Bot"));
+        assert_eq!(new_tagger.when(), tagger.when());
+
+        assert!(updates[2].signed);
+        assert!(!updates[2].resigned);
+        let v11 = repo.repo.find_tag(updates[2].new_oid).unwrap();
+        assert_eq!(v11.message(), Some("Signed 1.1\n"));
+
+        // unaffected tags are skipped
+        let none = rewrite_tags(&repo.repo, &names, |oid| oid, None).unwrap();
+        assert!(none.is_empty());
+    }
+}
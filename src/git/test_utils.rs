@@ -0,0 +1,69 @@
+use git2::{Oid, Repository, RepositoryInitOptions, Signature, Time};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Throwaway repository on `main` for exercising history rewrites.
+pub struct TestRepo {
+    pub dir: TempDir,
+    pub repo: Repository,
+}
+
+impl TestRepo {
+    pub fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        let mut opts = RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(dir.path(), &opts).unwrap();
+        Self { dir, repo }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn signature() -> Signature<'static> {
+        Signature::new(
+            "Test User",
+            "test@example.com",
+            &Time::new(1_700_000_000, 0),
+        )
+        .unwrap()
+    }
+
+    /// Writes `files` into the working tree and commits them on HEAD.
+    pub fn commit(&self, message: &str, files: &[(&str, &str)]) -> Oid {
+        let mut index = self.repo.index().unwrap();
+        for (path, content) in files {
+            let full_path = self.path().join(path);
+            if let Some(parent) = full_path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            std::fs::write(&full_path, content).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+
+        let tree = self.repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = self.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let sig = Self::signature();
+
+        self.repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    /// Creates a branch at HEAD and switches to it.
+    pub fn branch(&self, name: &str) {
+        let head = self.repo.head().unwrap().peel_to_commit().unwrap();
+        self.repo.branch(name, &head, false).unwrap();
+        self.checkout(name);
+    }
+
+    pub fn checkout(&self, name: &str) {
+        self.repo.set_head(&format!("refs/heads/{}", name)).unwrap();
+        self.repo
+            .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+    }
+}
@@ -0,0 +1,380 @@
+use anyhow::{Context, Result};
+use git2::{ObjectType, Oid, Repository, Tree};
+use glob::{MatchOptions, Pattern};
+use regex::bytes::Regex;
+use std::collections::{HashMap, HashSet};
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// What a text rule without its own `==>` replacement leaves behind.
+pub const DEFAULT_REPLACEMENT: &str = "***REMOVED***";
+
+/// Changes applied to every tree on the rewritten refs.
+#[derive(Clone, Debug, Default)]
+pub struct TreeFilter {
+    /// Paths or glob patterns to remove. A pattern without a `/` matches a
+    /// file or directory name at any depth, like in `.gitignore`; one with a
+    /// `/` is matched against the full path from the repository root.
+    pub purge: Vec<String>,
+    /// Blobs to remove wherever they appear.
+    pub strip_blobs: HashSet<Oid>,
+    /// Removes every blob larger than this many bytes.
+    pub max_blob_size: Option<u64>,
+    /// Text to scrub from file contents, one rule per entry in BFG's
+    /// `--replace-text` format: `secret`, `secret==>replacement` or
+    /// `regex:pass(word)?=\w+==>pass$1=`.
+    pub replace_text: Vec<String>,
+    /// Replacement for rules that don't give one; defaults to
+    /// [`DEFAULT_REPLACEMENT`].
+    pub replacement: Option<String>,
+    /// Drops commits that end up changing nothing once filtered.
+    pub prune_empty: bool,
+}
+
+/// Applies a [`TreeFilter`] to trees, caching results so that the many
+/// commits sharing a subtree only pay for it once.
+pub struct TreeRewriter {
+    anywhere: Vec<Pattern>,
+    rooted: Vec<Pattern>,
+    strip_blobs: HashSet<Oid>,
+    max_blob_size: Option<u64>,
+    rules: Vec<(Regex, Vec<u8>)>,
+    active: bool,
+    cache: HashMap<(Oid, String), Oid>,
+    /// Sizes of the blobs removed so far, for estimating the savings.
+    stripped: HashMap<Oid, u64>,
+    kept: HashSet<Oid>,
+    /// Text blobs rewritten by `rules`, old to new.
+    replaced: HashMap<Oid, Oid>,
+}
+
+impl TreeRewriter {
+    pub fn new(filter: &TreeFilter) -> Result<Self> {
+        let mut anywhere = Vec::new();
+        let mut rooted = Vec::new();
+        for pattern in &filter.purge {
+            let pattern = pattern.trim();
+            if pattern.is_empty() {
+                continue;
+            }
+            let trimmed = pattern.trim_start_matches('/').trim_end_matches('/');
+            let compiled = Pattern::new(trimmed)
+                .with_context(|| format!("Invalid path pattern: {}", pattern))?;
+            if pattern.contains('/') {
+                rooted.push(compiled);
+            } else {
+                anywhere.push(compiled);
+            }
+        }
+
+        let default = filter.replacement.as_deref().unwrap_or(DEFAULT_REPLACEMENT);
+        let mut rules = Vec::new();
+        for rule in &filter.replace_text {
+            if !rule.trim().is_empty() {
+                rules.push(parse_text_rule(rule, default)?);
+            }
+        }
+
+        Ok(Self {
+            active: !anywhere.is_empty()
+                || !rooted.is_empty()
+                || !filter.strip_blobs.is_empty()
+                || filter.max_blob_size.is_some()
+                || !rules.is_empty(),
+            anywhere,
+            rooted,
+            strip_blobs: filter.strip_blobs.clone(),
+            max_blob_size: filter.max_blob_size,
+            rules,
+            cache: HashMap::new(),
+            stripped: HashMap::new(),
+            kept: HashSet::new(),
+            replaced: HashMap::new(),
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// How many distinct blobs were stripped, and their combined size.
+    pub fn stripped(&self) -> (usize, u64) {
+        (self.stripped.len(), self.stripped.values().sum())
+    }
+
+    /// How many distinct blobs had text replaced.
+    pub fn replaced(&self) -> usize {
+        self.replaced.iter().filter(|(old, new)| old != new).count()
+    }
+
+    /// The filtered version of `tree`, or `tree` itself when nothing matches.
+    pub fn rewrite(&mut self, repo: &Repository, tree: Oid) -> Result<Oid> {
+        if !self.active {
+            return Ok(tree);
+        }
+        self.rewrite_at(repo, &repo.find_tree(tree)?, "")
+    }
+
+    fn rewrite_at(&mut self, repo: &Repository, tree: &Tree, prefix: &str) -> Result<Oid> {
+        let key = (tree.id(), prefix.to_string());
+        if let Some(cached) = self.cache.get(&key) {
+            return Ok(*cached);
+        }
+
+        // each kept entry with its possibly new id; `None` once removed
+        let mut kept = Vec::with_capacity(tree.len());
+        let mut changed = false;
+
+        for entry in tree.iter() {
+            let name = String::from_utf8_lossy(entry.name_bytes()).to_string();
+            let path = format!("{}{}", prefix, name);
+            if self.is_purged(&name, &path) {
+                changed = true;
+                kept.push(None);
+                continue;
+            }
+
+            let mut id = entry.id();
+            if entry.kind() == Some(ObjectType::Blob) {
+                if self.is_stripped(repo, id)? {
+                    changed = true;
+                    kept.push(None);
+                    continue;
+                }
+                id = self.replace_text(repo, id)?;
+                changed |= id != entry.id();
+            }
+            if entry.kind() == Some(ObjectType::Tree) {
+                let subtree = repo.find_tree(id)?;
+                id = self.rewrite_at(repo, &subtree, &format!("{}/", path))?;
+                if id != entry.id() {
+                    changed = true;
+                    // git never stores empty directories
+                    if repo.find_tree(id)?.is_empty() {
+                        kept.push(None);
+                        continue;
+                    }
+                }
+            }
+            kept.push(Some(id));
+        }
+
+        let result = if changed {
+            // libgit2 refuses legacy modes such as 100664, so entries are
+            // written with their normalised mode, as git itself would
+            let mut builder = repo.treebuilder(None)?;
+            for (entry, id) in tree.iter().zip(kept) {
+                if let Some(id) = id {
+                    builder.insert(entry.name_bytes(), id, entry.filemode())?;
+                }
+            }
+            builder.write()?
+        } else {
+            tree.id()
+        };
+        self.cache.insert(key, result);
+        Ok(result)
+    }
+
+    fn is_stripped(&mut self, repo: &Repository, blob: Oid) -> Result<bool> {
+        if self.stripped.contains_key(&blob) {
+            return Ok(true);
+        }
+        if self.kept.contains(&blob)
+            || (!self.strip_blobs.contains(&blob) && self.max_blob_size.is_none())
+        {
+            return Ok(false);
+        }
+
+        let (size, _) = repo.odb()?.read_header(blob)?;
+        let size = size as u64;
+        let strip =
+            self.strip_blobs.contains(&blob) || self.max_blob_size.is_some_and(|max| size > max);
+        if strip {
+            self.stripped.insert(blob, size);
+        } else {
+            self.kept.insert(blob);
+        }
+        Ok(strip)
+    }
+
+    /// Applies the text rules to a blob, leaving binary files alone.
+    fn replace_text(&mut self, repo: &Repository, blob: Oid) -> Result<Oid> {
+        if self.rules.is_empty() {
+            return Ok(blob);
+        }
+        if let Some(new) = self.replaced.get(&blob) {
+            return Ok(*new);
+        }
+
+        let object = repo.find_blob(blob)?;
+        let mut content = object.content().to_vec();
+        if !object.is_binary() {
+            for (regex, replacement) in &self.rules {
+                if regex.is_match(&content) {
+                    content = regex
+                        .replace_all(&content, replacement.as_slice())
+                        .into_owned();
+                }
+            }
+        }
+        let new = if content == object.content() {
+            blob
+        } else {
+            repo.blob(&content)?
+        };
+        self.replaced.insert(blob, new);
+        Ok(new)
+    }
+
+    fn is_purged(&self, name: &str, path: &str) -> bool {
+        self.anywhere
+            .iter()
+            .any(|pattern| pattern.matches_with(name, MATCH_OPTIONS))
+            || self
+                .rooted
+                .iter()
+                .any(|pattern| pattern.matches_with(path, MATCH_OPTIONS))
+    }
+}
+
+/// Parses one `--replace-text` rule into a pattern and its replacement.
+/// Literal rules match and replace verbatim; `regex:` rules may refer to
+/// capture groups as `$1`.
+fn parse_text_rule(rule: &str, default: &str) -> Result<(Regex, Vec<u8>)> {
+    let rule = rule.trim();
+    let (pattern, replacement) = match rule.rsplit_once("==>") {
+        Some((pattern, replacement)) => (pattern, Some(replacement)),
+        None => (rule, None),
+    };
+
+    match pattern.strip_prefix("regex:") {
+        Some(pattern) => {
+            let regex = Regex::new(pattern)
+                .with_context(|| format!("Invalid text pattern: {}", pattern))?;
+            let replacement = replacement
+                .unwrap_or(&default.replace('$', "$$"))
+                .to_string();
+            Ok((regex, replacement.into_bytes()))
+        }
+        None => {
+            let regex = Regex::new(&regex::escape(pattern))?;
+            let replacement = replacement.unwrap_or(default).replace('$', "$$");
+            Ok((regex, replacement.into_bytes()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TestRepo;
+    use std::path::Path;
+
+    #[test]
+    fn test_purge_patterns_match_names_and_rooted_paths() {
+        let repo = TestRepo::new();
+        let commit = repo.commit(
+            "files",
+            &[
+                (".env", "SECRET=1"),
+                ("app/.env", "SECRET=2"),
+                ("app/main.rs", "fn main() {}"),
+                ("config/keys/prod.pem", "key"),
+                ("config/app.toml", "x = 1"),
+            ],
+        );
+        let tree = repo.repo.find_commit(commit).unwrap().tree_id();
+
+        let mut rewriter = TreeRewriter::new(&TreeFilter {
+            purge: vec![".env".to_string(), "config/keys/".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let filtered = repo
+            .repo
+            .find_tree(rewriter.rewrite(&repo.repo, tree).unwrap())
+            .unwrap();
+
+        assert!(filtered.get_path(Path::new(".env")).is_err());
+        assert!(filtered.get_path(Path::new("app/.env")).is_err());
+        assert!(filtered.get_path(Path::new("app/main.rs")).is_ok());
+        assert!(filtered.get_path(Path::new("config/keys")).is_err());
+        assert!(filtered.get_path(Path::new("config/app.toml")).is_ok());
+
+        let mut untouched = TreeRewriter::new(&TreeFilter {
+            purge: vec!["*.log".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(untouched.rewrite(&repo.repo, tree).unwrap(), tree);
+    }
+
+    #[test]
+    fn test_legacy_file_modes_do_not_abort_filtering() {
+        let repo = TestRepo::new();
+        let blob = repo.repo.blob(b"1").unwrap();
+        let other = repo.repo.blob(b"2").unwrap();
+        // a tree as old git versions wrote it, with group-writable files
+        let mut raw = Vec::new();
+        for (mode, name, id) in [("100664", "a.txt", blob), ("100644", "b.log", other)] {
+            raw.extend_from_slice(format!("{} {}\0", mode, name).as_bytes());
+            raw.extend_from_slice(id.as_bytes());
+        }
+        let tree = repo
+            .repo
+            .odb()
+            .unwrap()
+            .write(ObjectType::Tree, &raw)
+            .unwrap();
+
+        let mut untouched = TreeRewriter::new(&TreeFilter {
+            purge: vec!["*.bin".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(untouched.rewrite(&repo.repo, tree).unwrap(), tree);
+
+        let mut purging = TreeRewriter::new(&TreeFilter {
+            purge: vec!["*.log".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let filtered = repo
+            .repo
+            .find_tree(purging.rewrite(&repo.repo, tree).unwrap())
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        let kept = filtered.get_name("a.txt").unwrap();
+        assert_eq!(kept.id(), blob);
+        assert_eq!(kept.filemode(), 0o100644);
+    }
+
+    #[test]
+    fn test_text_rules_follow_bfg_syntax() {
+        let (regex, replacement) = parse_text_rule("hunter2", DEFAULT_REPLACEMENT).unwrap();
+        assert_eq!(
+            regex.replace_all(b"pw = hunter2", replacement.as_slice()),
+            &b"pw = ***REMOVED***"[..]
+        );
+
+        // literals are not patterns, and `$` in a replacement is kept as is
+        let (regex, replacement) = parse_text_rule("a.b==>$1", "").unwrap();
+        assert_eq!(
+            regex.replace_all(b"a.b axb", replacement.as_slice()),
+            &b"$1 axb"[..]
+        );
+
+        let (regex, replacement) = parse_text_rule(r"regex:pass(word)?=\w+==>pass$1=", "").unwrap();
+        assert_eq!(
+            regex.replace_all(b"password=abc pass=x", replacement.as_slice()),
+            &b"password= pass="[..]
+        );
+
+        assert!(parse_text_rule("regex:(", "").is_err());
+    }
+}
@@ -0,0 +1,29 @@
+/// A ref as it was before a rewrite, kept in a backup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackupRef {
+    pub name: String,
+    /// Short hash and summary of the commit the ref pointed at.
+    pub target: String,
+    pub summary: String,
+    /// Short hash of where the ref points now, if it moved since or is gone.
+    pub current: Option<String>,
+}
+
+/// The pre-images saved by one rewrite.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackupInfo {
+    /// The backup's namespace below `refs/commit-rewriter/backup/`.
+    pub id: String,
+    /// When the backup was taken, in local time.
+    pub created: String,
+    pub refs: Vec<BackupRef>,
+    /// Whether the rewrite that took it recorded an old-to-new commit map.
+    pub commit_map: bool,
+}
+
+impl BackupInfo {
+    /// Refs that would move if this backup were restored.
+    pub fn changed(&self) -> usize {
+        self.refs.iter().filter(|r| r.current.is_some()).count()
+    }
+}
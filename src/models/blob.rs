@@ -0,0 +1,39 @@
+/// A file version found while scanning history for large blobs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlobInfo {
+    pub oid: String,
+    /// The first path the blob was seen at.
+    pub path: String,
+    pub size: u64,
+    /// The oldest commit on the scanned refs that contains the blob.
+    pub commit_hash: String,
+}
+
+/// Formats a byte count for display, e.g. `12.3 MB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+}
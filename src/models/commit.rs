@@ -1,28 +1,89 @@
+use crate::models::CommitTime;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Identity {
+    pub name: String,
+    pub email: String,
+}
+
+impl Identity {
+    pub fn new(name: String, email: String) -> Self {
+        Self { name, email }
+    }
+}
+
+impl std::fmt::Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CommitInfo {
     pub hash: String,
     pub short_hash: String,
-    pub message: String,
+    pub subject: String,
+    pub body: String,
+    pub raw_message: String,
     pub date: String,
-    pub author: String,
+    pub author: Identity,
+    pub author_time: CommitTime,
+    pub committer: Identity,
+    pub committer_time: CommitTime,
+    /// Remote-tracking branches that already contain this commit.
+    pub pushed_to: Vec<String>,
 }
 
 impl CommitInfo {
     pub fn new(
         hash: String,
         short_hash: String,
-        message: String,
-        date: String,
-        author: String,
+        raw_message: String,
+        author: Identity,
+        author_time: CommitTime,
+        committer: Identity,
+        committer_time: CommitTime,
     ) -> Self {
+        let (subject, body) = split_message(&raw_message);
+        let date = committer_time.utc_date();
         Self {
             hash,
             short_hash,
-            message,
+            subject,
+            body,
+            raw_message,
             date,
             author,
+            author_time,
+            committer,
+            committer_time,
+            pushed_to: Vec::new(),
         }
     }
+
+    /// The message as it should appear in the editor.
+    pub fn full_message(&self) -> &str {
+        self.raw_message.trim_end()
+    }
+}
+
+/// Splits a raw commit message the way git does: the subject is the first
+/// paragraph joined into one line, the body is everything after it.
+pub fn split_message(raw_message: &str) -> (String, String) {
+    let mut lines = raw_message
+        .lines()
+        .skip_while(|line| line.trim().is_empty());
+
+    let mut subject_lines = Vec::new();
+    for line in lines.by_ref() {
+        if line.trim().is_empty() {
+            break;
+        }
+        subject_lines.push(line.trim());
+    }
+
+    let body = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+    (subject_lines.join(" "), body)
 }
 
 #[cfg(test)]
@@ -34,15 +95,33 @@ mod tests {
         let commit = CommitInfo::new(
             "abc123def456".to_string(),
             "abc123de".to_string(),
-            "Initial commit".to_string(),
-            "2024-01-01 12:00".to_string(),
-            "John Doe".to_string(),
+            "Initial commit\n\nLonger description.\n\nSigned-off-by: John Doe <john@example.com>\n"
+                .to_string(),
+            Identity::new("John Doe".to_string(), "john@example.com".to_string()),
+            CommitTime::new(1_704_106_800, 60),
+            Identity::new("Jane Roe".to_string(), "jane@example.com".to_string()),
+            CommitTime::new(1_704_110_400, 0),
         );
 
         assert_eq!(commit.hash, "abc123def456");
         assert_eq!(commit.short_hash, "abc123de");
-        assert_eq!(commit.message, "Initial commit");
+        assert_eq!(commit.subject, "Initial commit");
+        assert_eq!(
+            commit.body,
+            "Longer description.\n\nSigned-off-by: John Doe <john@example.com>"
+        );
+        assert!(commit.raw_message.ends_with('\n'));
+        assert!(!commit.full_message().ends_with('\n'));
         assert_eq!(commit.date, "2024-01-01 12:00");
-        assert_eq!(commit.author, "John Doe");
+        assert_eq!(commit.author_time.format(), "2024-01-01 12:00:00 +0100");
+        assert_eq!(commit.author.name, "John Doe");
+        assert_eq!(commit.committer.to_string(), "Jane Roe <jane@example.com>");
+    }
+
+    #[test]
+    fn test_split_message_without_body() {
+        let (subject, body) = split_message("\nFix typo\nin readme\n");
+        assert_eq!(subject, "Fix typo in readme");
+        assert_eq!(body, "");
     }
 }
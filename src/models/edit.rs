@@ -0,0 +1,292 @@
+use crate::models::{CommitTime, Identity};
+use anyhow::Result;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeEdit {
+    /// Replace the timestamp and its UTC offset.
+    Set(CommitTime),
+    /// Move the timestamp by a number of seconds, keeping the original offset.
+    Shift(i64),
+}
+
+impl TimeEdit {
+    pub fn apply(&self, original: CommitTime) -> Result<CommitTime> {
+        match self {
+            TimeEdit::Set(time) => Ok(*time),
+            TimeEdit::Shift(seconds) => original.shifted(*seconds),
+        }
+    }
+}
+
+/// Fields to override on a commit's author or committer signature. Anything
+/// left as `None` keeps the original value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SignatureEdit {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub time: Option<TimeEdit>,
+}
+
+impl SignatureEdit {
+    /// Builds an edit holding only the fields of `wanted` that differ from
+    /// `original`.
+    pub fn diff(original: &Identity, wanted: &Identity) -> Self {
+        Self {
+            name: (wanted.name != original.name).then(|| wanted.name.clone()),
+            email: (wanted.email != original.email).then(|| wanted.email.clone()),
+            time: None,
+        }
+    }
+
+    pub fn with_time(mut self, time: Option<TimeEdit>) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.email.is_none() && self.time.is_none()
+    }
+
+    pub fn apply_time(&self, original: CommitTime) -> Result<CommitTime> {
+        match &self.time {
+            Some(edit) => edit.apply(original),
+            None => Ok(original),
+        }
+    }
+
+    pub fn apply(&self, original: &Identity) -> Identity {
+        Identity::new(
+            self.name.clone().unwrap_or_else(|| original.name.clone()),
+            self.email.clone().unwrap_or_else(|| original.email.clone()),
+        )
+    }
+
+    fn merge(&mut self, other: SignatureEdit) {
+        if other.name.is_some() {
+            self.name = other.name;
+        }
+        if other.email.is_some() {
+            self.email = other.email;
+        }
+        // consecutive shifts add up, anything else replaces; callers check a
+        // shift with `apply_time` first, so one that would overflow keeps the
+        // earlier edit
+        self.time = match (self.time, other.time) {
+            (Some(TimeEdit::Set(time)), Some(TimeEdit::Shift(seconds))) => Some(
+                time.shifted(seconds)
+                    .map_or(TimeEdit::Set(time), TimeEdit::Set),
+            ),
+            (Some(TimeEdit::Shift(a)), Some(TimeEdit::Shift(b))) => {
+                Some(TimeEdit::Shift(a.checked_add(b).unwrap_or(a)))
+            }
+            (current, None) => current,
+            (_, new) => new,
+        };
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingEdit {
+    pub commit_hash: String,
+    pub short_hash: String,
+    pub new_message: Option<String>,
+    pub author: SignatureEdit,
+    pub committer: SignatureEdit,
+    /// Newer commits folded into this one, oldest first.
+    pub squash: Vec<String>,
+    /// Remove the commit and replay its descendants onto its parent.
+    pub drop: bool,
+}
+
+impl PendingEdit {
+    pub fn new(commit_hash: String, short_hash: String) -> Self {
+        Self {
+            commit_hash,
+            short_hash,
+            new_message: None,
+            author: SignatureEdit::default(),
+            committer: SignatureEdit::default(),
+            squash: Vec::new(),
+            drop: false,
+        }
+    }
+
+    pub fn with_message(mut self, new_message: String) -> Self {
+        self.new_message = Some(new_message);
+        self
+    }
+
+    pub fn with_author(mut self, author: SignatureEdit) -> Self {
+        self.author = author;
+        self
+    }
+
+    pub fn with_committer(mut self, committer: SignatureEdit) -> Self {
+        self.committer = committer;
+        self
+    }
+
+    pub fn with_squash(mut self, squash: Vec<String>) -> Self {
+        self.squash = squash;
+        self
+    }
+
+    pub fn with_drop(mut self) -> Self {
+        self.drop = true;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.new_message.is_none()
+            && self.author.is_empty()
+            && self.committer.is_empty()
+            && self.squash.is_empty()
+            && !self.drop
+    }
+
+    /// One-line description for the queue list.
+    pub fn summary(&self) -> String {
+        if self.drop {
+            return "🗑 drop".to_string();
+        }
+        let mut parts = Vec::new();
+        if !self.squash.is_empty() {
+            parts.push(format!("🧩 squash {}", self.squash.len() + 1));
+        }
+        if let Some(message) = &self.new_message {
+            parts.push(format!("✎ {}", message.lines().next().unwrap_or("")));
+        }
+        if !self.author.is_empty() {
+            parts.push("👤 author".to_string());
+        }
+        if !self.committer.is_empty() {
+            parts.push("👤 committer".to_string());
+        }
+        if self.author.time.is_some() || self.committer.time.is_some() {
+            parts.push("🕒 date".to_string());
+        }
+        parts.join(" · ")
+    }
+}
+
+/// Adds `edit` to the queue, merging it into any earlier edit of the same
+/// commit.
+pub fn queue_edit(queue: &mut Vec<PendingEdit>, edit: PendingEdit) {
+    match queue
+        .iter_mut()
+        .find(|queued| queued.commit_hash == edit.commit_hash)
+    {
+        Some(queued) => {
+            if edit.new_message.is_some() {
+                queued.new_message = edit.new_message;
+            }
+            queued.author.merge(edit.author);
+            queued.committer.merge(edit.committer);
+            if !edit.squash.is_empty() {
+                queued.squash = edit.squash;
+            }
+            queued.drop |= edit.drop;
+        }
+        None => queue.push(edit),
+    }
+}
+
+/// Combines the messages of a squash group, oldest first, the way
+/// `git rebase --autosquash` does: `fixup!` messages are dropped and the
+/// `squash!` subject line is left out.
+pub fn combine_messages<S: AsRef<str>>(messages: &[S]) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for (i, message) in messages.iter().enumerate() {
+        let message = message.as_ref().trim();
+        if i > 0 && message.starts_with("fixup! ") {
+            continue;
+        }
+        if i > 0 && message.starts_with("squash! ") {
+            let rest = message.split_once('\n').map(|(_, rest)| rest.trim());
+            parts.extend(rest.filter(|rest| !rest.is_empty()));
+            continue;
+        }
+        parts.push(message);
+    }
+    parts.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_edit_merges_same_commit() {
+        let mut queue = Vec::new();
+        queue_edit(
+            &mut queue,
+            PendingEdit::new("abc".to_string(), "abc".to_string())
+                .with_message("First".to_string()),
+        );
+        queue_edit(
+            &mut queue,
+            PendingEdit::new("def".to_string(), "def".to_string())
+                .with_message("Other".to_string()),
+        );
+        queue_edit(
+            &mut queue,
+            PendingEdit::new("abc".to_string(), "abc".to_string()).with_author(SignatureEdit {
+                email: Some("new@example.com".to_string()),
+                ..Default::default()
+            }),
+        );
+        queue_edit(
+            &mut queue,
+            PendingEdit::new("abc".to_string(), "abc".to_string())
+                .with_message("Second".to_string()),
+        );
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue[0].new_message.as_deref(), Some("Second"));
+        assert_eq!(queue[0].author.email.as_deref(), Some("new@example.com"));
+        assert_eq!(queue[1].commit_hash, "def");
+    }
+
+    #[test]
+    fn test_signature_edit_diff_keeps_unchanged_fields() {
+        let original = Identity::new("Contractor".to_string(), "me@home.net".to_string());
+        let wanted = Identity::new("Contractor".to_string(), "me@company.com".to_string());
+
+        let edit = SignatureEdit::diff(&original, &wanted);
+        assert_eq!(edit.name, None);
+        assert_eq!(edit.email.as_deref(), Some("me@company.com"));
+
+        let other = Identity::new("Someone".to_string(), "someone@home.net".to_string());
+        assert_eq!(edit.apply(&other).to_string(), "Someone <me@company.com>");
+    }
+
+    #[test]
+    fn test_time_shifts_accumulate() {
+        let mut edit = SignatureEdit::default().with_time(Some(TimeEdit::Shift(3_600)));
+        edit.merge(SignatureEdit::default().with_time(Some(TimeEdit::Shift(-600))));
+
+        let original = CommitTime::new(1_700_000_000, 120);
+        assert_eq!(
+            edit.apply_time(original).unwrap(),
+            CommitTime::new(1_700_003_000, 120)
+        );
+
+        edit.merge(SignatureEdit::default().with_time(Some(TimeEdit::Set(original))));
+        edit.merge(SignatureEdit::default());
+        assert_eq!(edit.time, Some(TimeEdit::Set(original)));
+    }
+
+    #[test]
+    fn test_combine_messages_for_squash() {
+        let combined = combine_messages(&[
+            "Add parser\n\nInitial version.",
+            "fixup! Add parser",
+            "squash! Add parser\n\nHandle nested blocks.",
+            "Tweak docs",
+        ]);
+        assert_eq!(
+            combined,
+            "Add parser\n\nInitial version.\n\nHandle nested blocks.\n\nTweak docs"
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// How far an operation got before the app stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JournalPhase {
+    /// Rewriting commits; no ref has been touched yet.
+    Rewriting,
+    /// Moving refs to the rewritten commits, possibly only some of them.
+    UpdatingRefs,
+    /// Refs moved; waiting for the preview to be confirmed or cancelled.
+    AwaitingConfirm,
+}
+
+impl JournalPhase {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            JournalPhase::Rewriting => "was rewriting commits; no refs had moved yet",
+            JournalPhase::UpdatingRefs => "stopped while moving refs",
+            JournalPhase::AwaitingConfirm => "was waiting for the preview to be confirmed",
+        }
+    }
+}
+
+/// A ref an operation moves, as full hashes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalRef {
+    pub name: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// The record of an operation in flight, kept on disk until it's confirmed
+/// or rolled back.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Journal {
+    /// What the operation was, e.g. "Rewrite 3 commit(s)".
+    pub operation: String,
+    /// When it started, in local time.
+    pub started: String,
+    pub phase: JournalPhase,
+    pub refs: Vec<JournalRef>,
+    /// The undo-history backup the operation saved, dropped again if it is
+    /// rolled back.
+    #[serde(default)]
+    pub backup: Option<String>,
+}
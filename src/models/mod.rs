@@ -1,7 +1,31 @@
+pub mod backup;
+pub mod blob;
 pub mod commit;
+pub mod edit;
+pub mod journal;
 pub mod log;
+pub mod options;
+pub mod preflight;
 pub mod preview;
+pub mod push;
+pub mod refs;
+pub mod replace;
+pub mod secret;
+pub mod time;
+pub mod trailer;
 
-pub use commit::CommitInfo;
+pub use backup::{BackupInfo, BackupRef};
+pub use blob::{format_size, BlobInfo};
+pub use commit::{CommitInfo, Identity};
+pub use edit::{PendingEdit, SignatureEdit, TimeEdit};
+pub use journal::{Journal, JournalPhase, JournalRef};
 pub use log::{LogEntry, LogFilter, LogLevel};
-pub use preview::PreviewData;
+pub use options::RewriteOptions;
+pub use preflight::PreflightReport;
+pub use preview::{CommitChange, PreviewData};
+pub use push::{PushResult, PushTarget};
+pub use refs::{RefEntry, RefKind, RefSelection};
+pub use replace::{MessageChange, MessageReplace, ReplaceScope};
+pub use secret::SecretFinding;
+pub use time::CommitTime;
+pub use trailer::Trailer;
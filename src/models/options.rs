@@ -0,0 +1,37 @@
+use crate::models::RefSelection;
+
+/// Switches that apply to a whole rewrite rather than to a single commit.
+#[derive(Clone, Debug, Default)]
+pub struct RewriteOptions {
+    /// Sign recreated commits with `user.signingkey` instead of dropping
+    /// their signatures.
+    pub resign_commits: bool,
+    /// Move tags on rewritten commits, recreating annotated ones.
+    pub rewrite_tags: bool,
+    /// Sign recreated tags that were signed, instead of dropping the signature.
+    pub resign_tags: bool,
+    /// Paths or glob patterns removed from every commit's tree.
+    pub purge_paths: Vec<String>,
+    /// Leave out commits that the purge leaves without changes.
+    pub prune_empty: bool,
+    /// Ids of blobs removed from every tree.
+    pub strip_blobs: Vec<String>,
+    /// Removes every blob larger than this many bytes.
+    pub max_blob_size: Option<u64>,
+    /// Text rules scrubbed from every file, in BFG's `--replace-text` format.
+    pub replace_text: Vec<String>,
+    /// Replacement for rules that don't give their own.
+    pub replacement: Option<String>,
+    /// Copy git notes from rewritten commits to their replacements.
+    pub copy_notes: bool,
+    /// Notes refs to copy, space-separated names or globs; empty copies every
+    /// ref under `refs/notes/`.
+    pub notes_refs: String,
+    /// Replace hashes of rewritten commits quoted in messages, such as a
+    /// revert's "This reverts commit …", with the new hashes. Off unless
+    /// asked for, since it edits messages nobody touched.
+    pub update_hash_refs: bool,
+    /// Refs picked explicitly; overrides the current-branch / all-branches
+    /// switch when set.
+    pub ref_selection: Option<RefSelection>,
+}
@@ -0,0 +1,21 @@
+/// What the pre-flight checks found before a rewrite.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PreflightReport {
+    /// Problems that would make the rewrite fail or lose work.
+    pub errors: Vec<String>,
+    /// Things worth knowing that don't stop the rewrite.
+    pub warnings: Vec<String>,
+    /// Remote-tracking branches that already have commits being rewritten
+    /// and will need a force-push.
+    pub diverging: Vec<String>,
+}
+
+impl PreflightReport {
+    pub fn is_clear(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty() && self.diverging.is_empty()
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
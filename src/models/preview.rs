@@ -1,26 +1,101 @@
-#[derive(Clone, Debug)]
-pub struct PreviewData {
+#[derive(Clone, Debug, Default)]
+pub struct CommitChange {
     pub commit_hash: String,
     pub old_message: String,
     pub new_message: String,
+    pub old_author: String,
+    pub new_author: String,
+    pub old_committer: String,
+    pub new_committer: String,
+    pub old_author_date: String,
+    pub new_author_date: String,
+    pub old_committer_date: String,
+    pub new_committer_date: String,
+    /// Short hashes of the commits folded into this one.
+    pub squashed: Vec<String>,
+    pub dropped: bool,
+}
+
+impl CommitChange {
+    pub fn message_changed(&self) -> bool {
+        self.old_message != self.new_message
+    }
+
+    pub fn author_changed(&self) -> bool {
+        self.old_author != self.new_author
+    }
+
+    pub fn committer_changed(&self) -> bool {
+        self.old_committer != self.new_committer
+    }
+
+    pub fn author_date_changed(&self) -> bool {
+        self.old_author_date != self.new_author_date
+    }
+
+    pub fn committer_date_changed(&self) -> bool {
+        self.old_committer_date != self.new_committer_date
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PreviewData {
+    pub changes: Vec<CommitChange>,
     pub affected_commits: Vec<String>,
+    pub commit_map: Vec<(String, String)>,
     pub diff_output: String,
+    /// Short hashes of signed commits that were rewritten without a signature.
+    pub lost_signatures: Vec<String>,
+    /// The signing key used when the rewrite re-signed commits.
+    pub signed_with: Option<String>,
+    /// The replayed commits in their new order, oldest first.
+    pub reordered: Vec<String>,
+    /// Patterns purged from every tree.
+    pub purged_paths: Vec<String>,
+    /// Short hashes and summaries of commits pruned for being left empty.
+    pub pruned: Vec<String>,
+    /// Distinct blobs removed by size or id.
+    pub stripped_blobs: usize,
+    /// Their combined size, an upper bound on what a gc can reclaim.
+    pub stripped_bytes: u64,
+    /// How many text rules were applied, and how many blobs they changed.
+    pub text_rules: usize,
+    pub replaced_blobs: usize,
+    /// One line per tag that moves, e.g. `v1.0 1a2b3c4d → 5e6f7a8b (annotated)`.
+    pub moved_tags: Vec<String>,
+    /// Notes refs, relative to `refs/notes/`, and how many notes each carried over.
+    pub copied_notes: Vec<(String, usize)>,
+    /// The undo-history backup holding the refs' old positions.
+    pub backup: Option<String>,
+    /// One line per hash replaced in a message, e.g. `1a2b3c4d: abc1234 → def5678`.
+    pub hash_refs: Vec<String>,
 }
 
 impl PreviewData {
     pub fn new(
-        commit_hash: String,
-        old_message: String,
-        new_message: String,
+        changes: Vec<CommitChange>,
         affected_commits: Vec<String>,
+        commit_map: Vec<(String, String)>,
         diff_output: String,
     ) -> Self {
         Self {
-            commit_hash,
-            old_message,
-            new_message,
+            changes,
             affected_commits,
+            commit_map,
             diff_output,
+            lost_signatures: Vec::new(),
+            signed_with: None,
+            reordered: Vec::new(),
+            purged_paths: Vec::new(),
+            pruned: Vec::new(),
+            stripped_blobs: 0,
+            stripped_bytes: 0,
+            text_rules: 0,
+            replaced_blobs: 0,
+            moved_tags: Vec::new(),
+            copied_notes: Vec::new(),
+            backup: None,
+            hash_refs: Vec::new(),
         }
     }
 }
@@ -32,17 +107,25 @@ mod tests {
     #[test]
     fn test_preview_data_creation() {
         let preview = PreviewData::new(
-            "abc123".to_string(),
-            "Old message".to_string(),
-            "New message".to_string(),
+            vec![CommitChange {
+                commit_hash: "abc123".to_string(),
+                old_message: "Old message".to_string(),
+                new_message: "New message".to_string(),
+                ..Default::default()
+            }],
             vec!["refs/heads/main".to_string()],
+            vec![("abc123".to_string(), "def456".to_string())],
             "* abc123 New message".to_string(),
         );
 
-        assert_eq!(preview.commit_hash, "abc123");
-        assert_eq!(preview.old_message, "Old message");
-        assert_eq!(preview.new_message, "New message");
+        assert_eq!(preview.changes.len(), 1);
+        assert_eq!(preview.changes[0].commit_hash, "abc123");
+        assert_eq!(preview.changes[0].old_message, "Old message");
+        assert_eq!(preview.changes[0].new_message, "New message");
+        assert!(preview.changes[0].message_changed());
+        assert!(!preview.changes[0].author_changed());
         assert_eq!(preview.affected_commits.len(), 1);
+        assert_eq!(preview.commit_map.len(), 1);
         assert_eq!(preview.diff_output, "* abc123 New message");
     }
 }
@@ -0,0 +1,32 @@
+/// A rewritten branch and the remote branch it should replace.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PushTarget {
+    /// The local branch, e.g. `refs/heads/main`.
+    pub branch: String,
+    pub remote: String,
+    /// The branch on the remote, e.g. `refs/heads/main`.
+    pub remote_ref: String,
+    /// Where the remote-tracking branch pointed before the rewrite; the push
+    /// is refused unless the remote still has this.
+    pub lease: String,
+    pub new: String,
+}
+
+impl PushTarget {
+    /// `origin/main`-style name of the remote branch.
+    pub fn remote_name(&self) -> String {
+        format!(
+            "{}/{}",
+            self.remote,
+            self.remote_ref.trim_start_matches("refs/heads/")
+        )
+    }
+}
+
+/// What the remote said about one pushed ref.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PushResult {
+    pub target: PushTarget,
+    pub accepted: bool,
+    pub message: String,
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use glob::Pattern;
+use std::collections::BTreeSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RefKind {
+    Branch,
+    Tag,
+    Remote,
+    Notes,
+    Stash,
+    Other,
+}
+
+impl RefKind {
+    pub fn of(name: &str) -> Self {
+        if name.starts_with("refs/heads/") {
+            RefKind::Branch
+        } else if name.starts_with("refs/tags/") {
+            RefKind::Tag
+        } else if name.starts_with("refs/remotes/") {
+            RefKind::Remote
+        } else if name.starts_with("refs/notes/") {
+            RefKind::Notes
+        } else if name == "refs/stash" {
+            RefKind::Stash
+        } else {
+            RefKind::Other
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RefKind::Branch => "Branches",
+            RefKind::Tag => "Tags",
+            RefKind::Remote => "Remote-tracking",
+            RefKind::Notes => "Notes",
+            RefKind::Stash => "Stash",
+            RefKind::Other => "Other",
+        }
+    }
+}
+
+/// A ref that can take part in a rewrite.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RefEntry {
+    pub name: String,
+    pub short_name: String,
+    pub kind: RefKind,
+    /// Short hash of the commit the ref points at.
+    pub target: String,
+}
+
+/// An explicit choice of refs to rewrite, replacing the current-branch /
+/// all-branches switch.
+#[derive(Clone, Debug, Default)]
+pub struct RefSelection {
+    /// Full names of the refs ticked in the picker.
+    pub refs: BTreeSet<String>,
+    /// Glob patterns adding refs, matched against full or short names.
+    pub include: Vec<String>,
+    /// Glob patterns removing refs, applied last.
+    pub exclude: Vec<String>,
+}
+
+impl RefSelection {
+    /// The full names of the refs in `available` this selection picks.
+    pub fn resolve(&self, available: &[RefEntry]) -> Result<Vec<String>> {
+        let include = compile(&self.include)?;
+        let exclude = compile(&self.exclude)?;
+        let matches = |patterns: &[Pattern], entry: &RefEntry| {
+            patterns
+                .iter()
+                .any(|p| p.matches(&entry.name) || p.matches(&entry.short_name))
+        };
+
+        Ok(available
+            .iter()
+            .filter(|entry| self.refs.contains(&entry.name) || matches(&include, entry))
+            .filter(|entry| !matches(&exclude, entry))
+            .map(|entry| entry.name.clone())
+            .collect())
+    }
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Pattern>> {
+    patterns
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(|p| Pattern::new(p).with_context(|| format!("Invalid ref pattern: {}", p)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, short_name: &str) -> RefEntry {
+        RefEntry {
+            name: name.to_string(),
+            short_name: short_name.to_string(),
+            kind: RefKind::of(name),
+            target: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_selection_combines_ticks_and_patterns() {
+        let available = vec![
+            entry("refs/heads/main", "main"),
+            entry("refs/heads/release/1.0", "release/1.0"),
+            entry("refs/heads/release/2.0-rc", "release/2.0-rc"),
+            entry("refs/tags/v1.0", "v1.0"),
+            entry("refs/remotes/origin/main", "origin/main"),
+            entry("refs/stash", "stash"),
+        ];
+        assert_eq!(available[3].kind, RefKind::Tag);
+        assert_eq!(available[5].kind, RefKind::Stash);
+
+        let selection = RefSelection {
+            refs: BTreeSet::from(["refs/heads/main".to_string(), "refs/stash".to_string()]),
+            include: vec!["release/*".to_string(), "refs/tags/*".to_string()],
+            exclude: vec!["*-rc".to_string(), "stash".to_string()],
+        };
+        assert_eq!(
+            selection.resolve(&available).unwrap(),
+            vec![
+                "refs/heads/main",
+                "refs/heads/release/1.0",
+                "refs/tags/v1.0"
+            ]
+        );
+
+        let invalid = RefSelection {
+            include: vec!["[".to_string()],
+            ..Default::default()
+        };
+        assert!(invalid.resolve(&available).is_err());
+    }
+}
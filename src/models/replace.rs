@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+
+/// Which commits a find/replace runs over.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReplaceScope {
+    #[default]
+    SelectedRange,
+    SearchResults,
+}
+
+/// A regex find/replace over commit messages. The replacement may refer to
+/// capture groups as `$1` or `${name}`.
+#[derive(Clone, Debug, Default)]
+pub struct MessageReplace {
+    pub find: String,
+    pub replace: String,
+    pub case_insensitive: bool,
+    pub scope: ReplaceScope,
+}
+
+impl MessageReplace {
+    pub fn compile(&self) -> Result<Regex> {
+        if self.find.is_empty() {
+            anyhow::bail!("Enter a pattern to find");
+        }
+        RegexBuilder::new(&self.find)
+            .case_insensitive(self.case_insensitive)
+            .multi_line(true)
+            .build()
+            .with_context(|| format!("Invalid pattern: {}", self.find))
+    }
+}
+
+/// Before/after of one message, shown before the replacement is queued.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageChange {
+    pub commit_hash: String,
+    pub short_hash: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Returns the rewritten message, or `None` when the pattern leaves it as is.
+pub fn replace_message(regex: &Regex, replacement: &str, message: &str) -> Option<String> {
+    let after = regex.replace_all(message, replacement);
+    (after != message).then(|| after.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_with_capture_groups() {
+        let replace = MessageReplace {
+            find: r"\bOLD-(\d+)".to_string(),
+            replace: "NEW-$1".to_string(),
+            ..Default::default()
+        };
+        let regex = replace.compile().unwrap();
+
+        assert_eq!(
+            replace_message(&regex, &replace.replace, "Fix OLD-12\n\nRefs: OLD-7, OLD-8"),
+            Some("Fix NEW-12\n\nRefs: NEW-7, NEW-8".to_string())
+        );
+        assert_eq!(
+            replace_message(&regex, &replace.replace, "Nothing here"),
+            None
+        );
+
+        let case_insensitive = MessageReplace {
+            find: "^wip: ".to_string(),
+            case_insensitive: true,
+            ..Default::default()
+        };
+        let regex = case_insensitive.compile().unwrap();
+        assert_eq!(
+            replace_message(&regex, "", "WIP: parser\n\nwip: body"),
+            Some("parser\n\nbody".to_string())
+        );
+
+        assert!(MessageReplace {
+            find: "(".to_string(),
+            ..Default::default()
+        }
+        .compile()
+        .is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+
+/// A git timestamp: seconds since the epoch plus the committer's UTC offset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CommitTime {
+    pub seconds: i64,
+    pub offset_minutes: i32,
+}
+
+impl CommitTime {
+    pub fn new(seconds: i64, offset_minutes: i32) -> Self {
+        Self {
+            seconds,
+            offset_minutes,
+        }
+    }
+
+    /// Local time in the commit's own offset, e.g. `2024-01-01 14:00:00 +0200`.
+    pub fn format(&self) -> String {
+        match FixedOffset::east_opt(self.offset_minutes * 60)
+            .and_then(|offset| offset.timestamp_opt(self.seconds, 0).single())
+        {
+            Some(datetime) => datetime.format("%Y-%m-%d %H:%M:%S %z").to_string(),
+            None => format!("@{} {}", self.seconds, format_offset(self.offset_minutes)),
+        }
+    }
+
+    /// Short UTC form used in the commits list.
+    pub fn utc_date(&self) -> String {
+        DateTime::<Utc>::from_timestamp(self.seconds, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+
+    /// Parses `YYYY-MM-DD HH:MM[:SS] ±HHMM`. The offset may also be written
+    /// as `±HH:MM` or `Z`.
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let (datetime, offset) = input
+            .rsplit_once(' ')
+            .context("Expected a date, a time and a UTC offset")?;
+
+        let offset_minutes = parse_offset(offset)?;
+        let naive = NaiveDateTime::parse_from_str(datetime.trim(), "%Y-%m-%d %H:%M:%S")
+            .or_else(|_| NaiveDateTime::parse_from_str(datetime.trim(), "%Y-%m-%d %H:%M"))
+            .with_context(|| format!("Invalid date/time: {}", datetime.trim()))?;
+
+        let seconds = naive.and_utc().timestamp() - i64::from(offset_minutes) * 60;
+        Ok(Self::new(seconds, offset_minutes))
+    }
+
+    /// The same moment moved by `seconds`; fails if that leaves the range a
+    /// timestamp can hold.
+    pub fn shifted(&self, seconds: i64) -> Result<Self> {
+        let moved = self
+            .seconds
+            .checked_add(seconds)
+            .with_context(|| format!("Invalid shift: {}s moves the date out of range", seconds))?;
+        Ok(Self::new(moved, self.offset_minutes))
+    }
+}
+
+fn format_offset(offset_minutes: i32) -> String {
+    let sign = if offset_minutes < 0 { '-' } else { '+' };
+    let minutes = offset_minutes.abs();
+    format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60)
+}
+
+fn parse_offset(input: &str) -> Result<i32> {
+    if input == "Z" {
+        return Ok(0);
+    }
+
+    let (sign, digits) = if let Some(rest) = input.strip_prefix('+') {
+        (1, rest)
+    } else if let Some(rest) = input.strip_prefix('-') {
+        (-1, rest)
+    } else {
+        anyhow::bail!("Invalid UTC offset: {}", input);
+    };
+    let digits = digits.replace(':', "");
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        anyhow::bail!("Invalid UTC offset: {}", input);
+    }
+
+    let hours: i32 = digits[..2].parse()?;
+    let minutes: i32 = digits[2..].parse()?;
+    if hours > 14 || minutes > 59 {
+        anyhow::bail!("UTC offset out of range: {}", input);
+    }
+    Ok(sign * (hours * 60 + minutes))
+}
+
+/// Parses a signed duration such as `+2h`, `-30m`, `1d 6h` or `+90s`.
+pub fn parse_shift(input: &str) -> Result<i64> {
+    let input = input.trim();
+    let (sign, rest) = match input.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, input.strip_prefix('+').unwrap_or(input)),
+    };
+
+    let mut total = 0i64;
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'd' | 'h' | 'm' | 's' => {
+                let value: i64 = number
+                    .parse()
+                    .with_context(|| format!("Invalid shift: {}", input))?;
+                let unit = match c {
+                    'd' => 86_400,
+                    'h' => 3_600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                total = value
+                    .checked_mul(unit)
+                    .and_then(|seconds| total.checked_add(seconds))
+                    .with_context(|| format!("Invalid shift: {} is too large", input))?;
+                number.clear();
+            }
+            ' ' => {}
+            _ => anyhow::bail!("Invalid shift: {}", input),
+        }
+    }
+
+    if !number.is_empty() || total == 0 {
+        anyhow::bail!("Invalid shift: {} (use units d, h, m, s)", input);
+    }
+    Ok(sign * total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_and_parse_round_trip() {
+        let time = CommitTime::new(1_700_000_000, 120);
+        assert_eq!(time.format(), "2023-11-15 00:13:20 +0200");
+        assert_eq!(CommitTime::parse(&time.format()).unwrap(), time);
+        assert_eq!(
+            CommitTime::parse("2023-11-14 22:13:20 Z").unwrap(),
+            CommitTime::new(1_700_000_000, 0)
+        );
+        assert_eq!(
+            CommitTime::parse("2023-11-14 17:43 -04:30").unwrap(),
+            CommitTime::new(1_699_999_980, -270)
+        );
+        assert!(CommitTime::parse("2023-11-14 22:13:20").is_err());
+    }
+
+    #[test]
+    fn test_parse_shift() {
+        assert_eq!(parse_shift("+2h").unwrap(), 7_200);
+        assert_eq!(parse_shift("-30m").unwrap(), -1_800);
+        assert_eq!(parse_shift("1d 6h").unwrap(), 108_000);
+        assert!(parse_shift("2").is_err());
+        assert!(parse_shift("2w").is_err());
+        assert!(parse_shift("999999999999999d").is_err());
+        assert!(parse_shift("9223372036854775807s 1s").is_err());
+
+        let time = CommitTime::new(1_700_000_000, 0);
+        assert_eq!(
+            time.shifted(-600).unwrap(),
+            CommitTime::new(1_699_999_400, 0)
+        );
+        assert!(time.shifted(i64::MAX).is_err());
+    }
+}
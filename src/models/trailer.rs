@@ -0,0 +1,185 @@
+/// Git-generated prefixes that let a trailer block contain other lines too.
+const RECOGNIZED_PREFIXES: [&str; 2] = ["Signed-off-by: ", "(cherry picked from commit "];
+
+pub const TRAILER_PRESETS: [&str; 5] = [
+    "Signed-off-by",
+    "Co-authored-by",
+    "Reviewed-by",
+    "Acked-by",
+    "Refs",
+];
+
+/// One line of a message's trailer block. Lines that are not `Key: value`
+/// (allowed next to git-generated trailers) keep an empty key and their raw
+/// text as the value, so they survive a round trip.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+impl Trailer {
+    pub fn new(key: &str, value: &str) -> Self {
+        Self {
+            key: key.trim().to_string(),
+            value: value.trim().to_string(),
+        }
+    }
+
+    pub fn is_trailer(&self) -> bool {
+        !self.key.is_empty()
+    }
+
+    fn same_as(&self, other: &Trailer) -> bool {
+        self.key.eq_ignore_ascii_case(&other.key) && self.value == other.value
+    }
+}
+
+impl std::fmt::Display for Trailer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_trailer() {
+            write!(f, "{}: {}", self.key, self.value)
+        } else {
+            write!(f, "{}", self.value)
+        }
+    }
+}
+
+/// Splits a message into everything before its trailer block and the
+/// trailers themselves, following `git interpret-trailers`: only the last
+/// paragraph is considered, never the subject, and it must consist of
+/// trailers only — or contain a git-generated one and be at least 25%
+/// trailers.
+pub fn split_trailers(message: &str) -> (String, Vec<Trailer>) {
+    let message = message.trim_end();
+    let lines: Vec<&str> = message.lines().collect();
+
+    let Some(blank) = lines.iter().rposition(|line| line.trim().is_empty()) else {
+        return (message.to_string(), Vec::new());
+    };
+    if lines[..blank].iter().all(|line| line.trim().is_empty()) {
+        return (message.to_string(), Vec::new());
+    }
+
+    let mut trailers: Vec<Trailer> = Vec::new();
+    let mut trailer_lines = 0;
+    let mut other_lines = 0;
+    let mut recognized = false;
+
+    for line in &lines[blank + 1..] {
+        let continues = line.starts_with([' ', '\t']);
+        if continues {
+            if let Some(last) = trailers.last_mut().filter(|t| t.is_trailer()) {
+                last.value.push('\n');
+                last.value.push_str(line);
+                continue;
+            }
+        }
+
+        match parse_trailer_line(line) {
+            Some(trailer) if !continues => {
+                trailer_lines += 1;
+                recognized |= RECOGNIZED_PREFIXES
+                    .iter()
+                    .any(|prefix| line.starts_with(prefix));
+                trailers.push(trailer);
+            }
+            _ => {
+                other_lines += 1;
+                trailers.push(Trailer {
+                    key: String::new(),
+                    value: line.to_string(),
+                });
+            }
+        }
+    }
+
+    let is_block =
+        (trailer_lines > 0 && other_lines == 0) || (recognized && trailer_lines * 3 >= other_lines);
+    if !is_block {
+        return (message.to_string(), Vec::new());
+    }
+
+    let body = lines[..blank].join("\n").trim_end().to_string();
+    (body, trailers)
+}
+
+/// Reassembles a message from the text before the trailers and the trailers.
+pub fn join_trailers(body: &str, trailers: &[Trailer]) -> String {
+    let body = body.trim_end();
+    if trailers.is_empty() {
+        return body.to_string();
+    }
+
+    let block = trailers
+        .iter()
+        .map(|trailer| trailer.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{}\n\n{}", body, block)
+}
+
+/// Appends `trailer` unless the message already carries the same one.
+/// Returns `None` when nothing was added.
+pub fn add_trailer(message: &str, trailer: Trailer) -> Option<String> {
+    let (body, mut trailers) = split_trailers(message);
+    if trailers.iter().any(|existing| existing.same_as(&trailer)) {
+        return None;
+    }
+    trailers.push(trailer);
+    Some(join_trailers(&body, &trailers))
+}
+
+/// Keys may contain letters, digits and dashes; whitespace is allowed
+/// before the colon.
+fn parse_trailer_line(line: &str) -> Option<Trailer> {
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim_end();
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    Some(Trailer::new(key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_trailers_follows_interpret_trailers() {
+        let (body, trailers) = split_trailers(
+            "Fix parser\n\nLonger explanation.\n\nRefs: PROJ-12\nCo-authored-by: A <a@x.io>\n  (pairing)\nSigned-off-by: B <b@x.io>\n",
+        );
+        assert_eq!(body, "Fix parser\n\nLonger explanation.");
+        assert_eq!(trailers.len(), 3);
+        assert_eq!(trailers[1].key, "Co-authored-by");
+        assert_eq!(trailers[1].value, "A <a@x.io>\n  (pairing)");
+
+        // the subject is never a trailer block
+        assert!(split_trailers("Refs: PROJ-12").1.is_empty());
+        // a paragraph of prose is not a trailer block
+        assert!(split_trailers("Fix\n\nNote: this is prose\nand more prose")
+            .1
+            .is_empty());
+        // ...unless a git-generated trailer vouches for it
+        let (_, mixed) = split_trailers("Fix\n\nSigned-off-by: B <b@x.io>\nsee above");
+        assert_eq!(mixed.len(), 2);
+        assert!(!mixed[1].is_trailer());
+    }
+
+    #[test]
+    fn test_join_and_add_trailer_round_trip() {
+        let message =
+            "Fix parser\n\nBody text.\n\nRefs: PROJ-12\n  continued\nAcked-by: C <c@x.io>";
+        let (body, trailers) = split_trailers(message);
+        assert_eq!(join_trailers(&body, &trailers), message);
+
+        let signoff = Trailer::new("Signed-off-by", "Test User <test@example.com>");
+        let signed = add_trailer("Fix parser", signoff.clone()).unwrap();
+        assert_eq!(
+            signed,
+            "Fix parser\n\nSigned-off-by: Test User <test@example.com>"
+        );
+        assert!(add_trailer(&signed, signoff).is_none());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::git::commit_map::MapFormat;
+use crate::models::BackupInfo;
+use eframe::egui;
+
+#[derive(Clone, Debug, Default)]
+pub struct BackupForm {
+    pub backups: Vec<BackupInfo>,
+    /// Backup whose delete button was clicked once and awaits a second click.
+    pub confirm_delete: Option<String>,
+}
+
+pub struct BackupsPanelResult {
+    pub refresh_clicked: bool,
+    pub restore: Option<String>,
+    pub delete: Option<String>,
+    /// Backup whose commit map should be exported, and in which format.
+    pub export: Option<(String, MapFormat)>,
+}
+
+pub fn render_backups_panel(
+    ui: &mut egui::Ui,
+    form: &mut BackupForm,
+    has_repo: bool,
+    is_processing: bool,
+) -> BackupsPanelResult {
+    let mut result = BackupsPanelResult {
+        refresh_clicked: false,
+        restore: None,
+        delete: None,
+        export: None,
+    };
+
+    egui::CollapsingHeader::new(
+        egui::RichText::new(format!("🕘 Undo history ({})", form.backups.len())).size(12.0),
+    )
+    .show(ui, |ui| {
+        if ui
+            .add_enabled(has_repo, egui::Button::new("🔄 Refresh").small())
+            .clicked()
+        {
+            result.refresh_clicked = true;
+        }
+
+        if form.backups.is_empty() {
+            ui.label(
+                egui::RichText::new("No backups yet; every rewrite adds one")
+                    .size(11.0)
+                    .italics()
+                    .color(egui::Color32::GRAY),
+            );
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("backup_history")
+            .max_height(260.0)
+            .show(ui, |ui| {
+                for backup in &form.backups {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(&backup.created).monospace().size(11.0))
+                            .on_hover_text(format!("refs/commit-rewriter/backup/{}/", backup.id));
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{} ref(s), {} would move",
+                                backup.refs.len(),
+                                backup.changed()
+                            ))
+                            .size(11.0)
+                            .color(egui::Color32::GRAY),
+                        );
+
+                        if ui
+                            .add_enabled(
+                                !is_processing && backup.changed() > 0,
+                                egui::Button::new("↩ Restore").small(),
+                            )
+                            .on_hover_text(
+                                "Move these refs back; where they are now is saved as a new backup",
+                            )
+                            .clicked()
+                        {
+                            result.restore = Some(backup.id.clone());
+                        }
+
+                        if backup.commit_map {
+                            for format in [MapFormat::Tsv, MapFormat::Json] {
+                                let label = format!("💾 {}", format.extension().to_uppercase());
+                                if ui
+                                    .add(egui::Button::new(label).small())
+                                    .on_hover_text("Export which new commit replaced each old one")
+                                    .clicked()
+                                {
+                                    result.export = Some((backup.id.clone(), format));
+                                }
+                            }
+                        }
+
+                        let confirming = form.confirm_delete.as_deref() == Some(&backup.id);
+                        let label = if confirming { "Really delete?" } else { "🗑" };
+                        if ui
+                            .add_enabled(!is_processing, egui::Button::new(label).small())
+                            .on_hover_text(
+                                "Forget this backup; its commits may be garbage collected",
+                            )
+                            .clicked()
+                        {
+                            if confirming {
+                                result.delete = Some(backup.id.clone());
+                            } else {
+                                form.confirm_delete = Some(backup.id.clone());
+                            }
+                        }
+                    });
+
+                    for entry in &backup.refs {
+                        ui.horizontal(|ui| {
+                            ui.add_space(16.0);
+                            ui.label(egui::RichText::new(&entry.name).monospace().size(11.0));
+                            ui.label(
+                                egui::RichText::new(&entry.target)
+                                    .monospace()
+                                    .size(11.0)
+                                    .color(egui::Color32::from_rgb(150, 200, 255)),
+                            );
+                            ui.label(
+                                egui::RichText::new(&entry.summary)
+                                    .size(11.0)
+                                    .color(egui::Color32::GRAY),
+                            );
+                            if let Some(current) = &entry.current {
+                                ui.label(
+                                    egui::RichText::new(format!("(now {})", current))
+                                        .monospace()
+                                        .size(10.0)
+                                        .color(egui::Color32::from_rgb(255, 180, 50)),
+                                );
+                            }
+                        });
+                    }
+                    ui.add_space(3.0);
+                }
+            });
+    });
+
+    result
+}
@@ -0,0 +1,148 @@
+use crate::models::{format_size, BlobInfo};
+use eframe::egui;
+use std::collections::HashSet;
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+#[derive(Clone, Debug)]
+pub struct BlobForm {
+    pub threshold_mb: f64,
+    pub prune_empty: bool,
+    /// Ids of the blobs ticked in the scan results.
+    pub selected: HashSet<String>,
+    pub results: Option<Vec<BlobInfo>>,
+}
+
+impl Default for BlobForm {
+    fn default() -> Self {
+        Self {
+            threshold_mb: 10.0,
+            prune_empty: false,
+            selected: HashSet::new(),
+            results: None,
+        }
+    }
+}
+
+impl BlobForm {
+    pub fn threshold_bytes(&self) -> u64 {
+        (self.threshold_mb * BYTES_PER_MB) as u64
+    }
+}
+
+pub struct BlobsPanelResult {
+    pub scan_clicked: bool,
+    pub strip_selected_clicked: bool,
+    pub strip_over_clicked: bool,
+}
+
+pub fn render_blobs_panel(
+    ui: &mut egui::Ui,
+    form: &mut BlobForm,
+    has_repo: bool,
+    is_processing: bool,
+) -> BlobsPanelResult {
+    let mut result = BlobsPanelResult {
+        scan_clicked: false,
+        strip_selected_clicked: false,
+        strip_over_clicked: false,
+    };
+
+    egui::CollapsingHeader::new(egui::RichText::new("📦 Large blobs").size(12.0)).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !is_processing && has_repo,
+                    egui::Button::new("🔎 Scan history").small(),
+                )
+                .on_hover_text("List the largest files ever committed on any branch")
+                .clicked()
+            {
+                result.scan_clicked = true;
+            }
+            ui.checkbox(&mut form.prune_empty, "Prune commits left empty");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Over");
+            ui.add(
+                egui::DragValue::new(&mut form.threshold_mb)
+                    .speed(0.5)
+                    .range(0.01..=10_000.0)
+                    .suffix(" MB"),
+            );
+            if ui
+                .add_enabled(
+                    !is_processing && has_repo,
+                    egui::Button::new("📦 Strip all over size").small(),
+                )
+                .on_hover_text("Remove every blob above this size from the selected branches")
+                .clicked()
+            {
+                result.strip_over_clicked = true;
+            }
+            if ui
+                .add_enabled(
+                    !is_processing && !form.selected.is_empty(),
+                    egui::Button::new(format!("📦 Strip {} selected", form.selected.len())).small(),
+                )
+                .clicked()
+            {
+                result.strip_selected_clicked = true;
+            }
+        });
+
+        let Some(blobs) = &form.results else {
+            return;
+        };
+        if blobs.is_empty() {
+            ui.label(
+                egui::RichText::new("No blobs in history")
+                    .size(11.0)
+                    .italics()
+                    .color(egui::Color32::GRAY),
+            );
+            return;
+        }
+
+        let threshold = form.threshold_bytes();
+        egui::ScrollArea::vertical()
+            .id_source("blob_results")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for blob in blobs {
+                    ui.horizontal(|ui| {
+                        let mut checked = form.selected.contains(&blob.oid);
+                        if ui.checkbox(&mut checked, "").changed() {
+                            if checked {
+                                form.selected.insert(blob.oid.clone());
+                            } else {
+                                form.selected.remove(&blob.oid);
+                            }
+                        }
+                        let color = if blob.size > threshold {
+                            egui::Color32::from_rgb(255, 180, 50)
+                        } else {
+                            egui::Color32::GRAY
+                        };
+                        ui.label(
+                            egui::RichText::new(format!("{:>9}", format_size(blob.size)))
+                                .monospace()
+                                .size(11.0)
+                                .color(color),
+                        );
+                        ui.label(
+                            egui::RichText::new(&blob.commit_hash[..8])
+                                .monospace()
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(150, 200, 255)),
+                        )
+                        .on_hover_text("First commit containing this blob");
+                        ui.label(egui::RichText::new(&blob.path).monospace().size(11.0));
+                    });
+                }
+            });
+    });
+
+    result
+}
@@ -1,16 +1,24 @@
-use crate::models::CommitInfo;
+use crate::models::{CommitInfo, PendingEdit};
 use eframe::egui;
 
 pub struct CommitsListResult {
-    pub selected_commit: Option<(usize, String, String)>, // (index, short_hash, message)
+    pub selected_commit: Option<(usize, String, String)>, // (index, short_hash, subject)
+    pub extend_selection: bool,
     pub load_more_clicked: bool,
     pub search_changed: bool,
+    pub move_up_clicked: bool,
+    pub move_down_clicked: bool,
+    pub reset_order_clicked: bool,
+    pub drop_clicked: bool,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_commits_list(
     ui: &mut egui::Ui,
     commits: &[CommitInfo],
-    selected_index: Option<usize>,
+    selected_range: Option<(usize, usize)>,
+    pending_edits: &[PendingEdit],
+    moved_commits: &[String],
     search_query: &mut String,
     is_processing: bool,
     loading_more: bool,
@@ -18,8 +26,13 @@ pub fn render_commits_list(
 ) -> CommitsListResult {
     let mut result = CommitsListResult {
         selected_commit: None,
+        extend_selection: false,
         load_more_clicked: false,
         search_changed: false,
+        move_up_clicked: false,
+        move_down_clicked: false,
+        reset_order_clicked: false,
+        drop_clicked: false,
     };
 
     ui.horizontal(|ui| {
@@ -43,6 +56,41 @@ pub fn render_commits_list(
                         .color(egui::Color32::GRAY),
                 );
             }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let can_move = !is_processing && selected_range.is_some();
+                if ui
+                    .add_enabled(can_move, egui::Button::new("🗑").small())
+                    .on_hover_text("Drop the selected commit(s) and replay their descendants")
+                    .clicked()
+                {
+                    result.drop_clicked = true;
+                }
+                if ui
+                    .add_enabled(
+                        !is_processing && !moved_commits.is_empty(),
+                        egui::Button::new("↺").small(),
+                    )
+                    .on_hover_text("Restore the original order")
+                    .clicked()
+                {
+                    result.reset_order_clicked = true;
+                }
+                if ui
+                    .add_enabled(can_move, egui::Button::new("⬇").small())
+                    .on_hover_text("Move the selected commit down (earlier in history)")
+                    .clicked()
+                {
+                    result.move_down_clicked = true;
+                }
+                if ui
+                    .add_enabled(can_move, egui::Button::new("⬆").small())
+                    .on_hover_text("Move the selected commit up (later in history)")
+                    .clicked()
+                {
+                    result.move_up_clicked = true;
+                }
+            });
         }
     });
 
@@ -60,11 +108,11 @@ pub fn render_commits_list(
                 result.search_changed = true;
             }
 
-            if !search_query.is_empty() {
-                if ui.small_button("✖").on_hover_text("Clear search").clicked() {
-                    search_query.clear();
-                    result.search_changed = true;
-                }
+            if !search_query.is_empty()
+                && ui.small_button("✖").on_hover_text("Clear search").clicked()
+            {
+                search_query.clear();
+                result.search_changed = true;
             }
         });
         ui.add_space(2.0);
@@ -111,11 +159,41 @@ pub fn render_commits_list(
                 } else {
                     for (original_index, commit) in filtered.iter() {
                         let i = *original_index;
-                        let is_selected = selected_index == Some(i);
+                        let is_selected = selected_range
+                            .map(|(start, end)| (start..=end).contains(&i))
+                            .unwrap_or(false);
+                        let queued = pending_edits
+                            .iter()
+                            .find(|edit| edit.commit_hash == commit.hash);
+                        let is_dropped = queued.is_some_and(|edit| edit.drop);
+                        let is_queued = queued.is_some();
+                        let is_folded = pending_edits
+                            .iter()
+                            .any(|edit| edit.squash.contains(&commit.hash));
+                        let is_moved = moved_commits.contains(&commit.hash);
 
                         let full_text = format!(
-                            "{} │ {} │ {}",
-                            commit.short_hash, commit.date, commit.message
+                            "{}{}{} │ {} │ {}{}",
+                            if is_dropped {
+                                "✖ "
+                            } else if is_queued {
+                                "✎ "
+                            } else if is_folded {
+                                "⤵ "
+                            } else if is_moved {
+                                "↕ "
+                            } else {
+                                ""
+                            },
+                            commit.short_hash,
+                            if commit.pushed_to.is_empty() {
+                                ""
+                            } else {
+                                " ☁"
+                            },
+                            commit.date,
+                            commit.subject,
+                            if commit.body.is_empty() { "" } else { " ¶" }
                         );
 
                         // Create selectable with highlighted matches
@@ -135,13 +213,26 @@ pub fn render_commits_list(
 
                         if response.clicked() && !is_processing {
                             result.selected_commit =
-                                Some((i, commit.short_hash.clone(), commit.message.clone()));
+                                Some((i, commit.short_hash.clone(), commit.subject.clone()));
+                            result.extend_selection = ui.input(|input| input.modifiers.shift);
                         }
 
-                        response.on_hover_text(format!(
-                            "Hash: {}\nAuthor: {}\nDate: {}\nMessage: {}",
-                            commit.hash, commit.author, commit.date, commit.message
-                        ));
+                        let mut hover_text = format!(
+                            "Hash: {}\nAuthor: {}\nCommitter: {}\nDate: {}\nMessage: {}",
+                            commit.hash,
+                            commit.author,
+                            commit.committer,
+                            commit.date,
+                            commit.subject
+                        );
+                        if !commit.pushed_to.is_empty() {
+                            hover_text
+                                .push_str(&format!("\nPushed to: {}", commit.pushed_to.join(", ")));
+                        }
+                        if !commit.body.is_empty() {
+                            hover_text.push_str(&format!("\n\n{}", commit.body));
+                        }
+                        response.on_hover_text(hover_text);
                     }
 
                     // Only show "Load more" button if there are potentially more commits
@@ -182,7 +273,7 @@ pub fn render_commits_list(
     result
 }
 
-fn filter_commits<'a>(commits: &'a [CommitInfo], query: &str) -> Vec<(usize, &'a CommitInfo)> {
+pub fn filter_commits<'a>(commits: &'a [CommitInfo], query: &str) -> Vec<(usize, &'a CommitInfo)> {
     if query.trim().is_empty() {
         commits.iter().enumerate().collect()
     } else {
@@ -191,9 +282,10 @@ fn filter_commits<'a>(commits: &'a [CommitInfo], query: &str) -> Vec<(usize, &'a
             .iter()
             .enumerate()
             .filter(|(_, commit)| {
-                commit.message.to_lowercase().contains(&query_lower)
+                commit.raw_message.to_lowercase().contains(&query_lower)
                     || commit.hash.to_lowercase().contains(&query_lower)
-                    || commit.author.to_lowercase().contains(&query_lower)
+                    || commit.author.name.to_lowercase().contains(&query_lower)
+                    || commit.author.email.to_lowercase().contains(&query_lower)
             })
             .collect()
     }
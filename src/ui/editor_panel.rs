@@ -1,17 +1,82 @@
+use crate::models::trailer::{join_trailers, split_trailers, TRAILER_PRESETS};
+use crate::models::{Identity, PendingEdit, Trailer};
 use eframe::egui;
 
+/// Author and committer fields shown for the selected commit.
+#[derive(Clone, Debug)]
+pub struct IdentityForm {
+    pub author: Identity,
+    pub author_date: String,
+    pub committer: Identity,
+    pub committer_date: String,
+    pub shift: String,
+    pub shift_author: bool,
+    pub shift_committer: bool,
+}
+
+impl Default for IdentityForm {
+    fn default() -> Self {
+        Self {
+            author: Identity::default(),
+            author_date: String::new(),
+            committer: Identity::default(),
+            committer_date: String::new(),
+            shift: String::new(),
+            shift_author: true,
+            shift_committer: true,
+        }
+    }
+}
+
+/// The "add trailer" row below the message editor.
+#[derive(Clone, Debug)]
+pub struct TrailerForm {
+    pub key: String,
+    pub value: String,
+}
+
+impl Default for TrailerForm {
+    fn default() -> Self {
+        Self {
+            key: TRAILER_PRESETS[0].to_string(),
+            value: String::new(),
+        }
+    }
+}
+
 pub struct EditorPanelResult {
     pub apply_clicked: bool,
+    pub queue_clicked: bool,
+    pub apply_identity_to_range: bool,
+    pub shift_dates_clicked: bool,
+    pub signoff_clicked: bool,
+    pub signoff_range_clicked: bool,
+    pub squash_clicked: bool,
+    pub removed_edit: Option<usize>,
+    pub clear_queue_clicked: bool,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_editor_panel(
     ui: &mut egui::Ui,
     new_message: &mut String,
+    identity: &mut IdentityForm,
+    trailer_form: &mut TrailerForm,
     selected_index: Option<usize>,
+    range_len: usize,
+    pending_edits: &[PendingEdit],
     is_processing: bool,
 ) -> EditorPanelResult {
     let mut result = EditorPanelResult {
         apply_clicked: false,
+        queue_clicked: false,
+        apply_identity_to_range: false,
+        shift_dates_clicked: false,
+        signoff_clicked: false,
+        signoff_range_clicked: false,
+        squash_clicked: false,
+        removed_edit: None,
+        clear_queue_clicked: false,
     };
 
     ui.vertical(|ui| {
@@ -50,28 +115,174 @@ pub fn render_editor_panel(
 
         let text_edit = egui::TextEdit::multiline(new_message)
             .desired_width(f32::INFINITY)
-            .desired_rows(4)
-            .hint_text("Enter new commit message...")
+            .desired_rows(6)
+            .hint_text("Subject line\n\nOptional body and trailers...")
             .font(egui::TextStyle::Monospace);
 
         ui.add_enabled(!is_processing, text_edit);
 
         ui.add_space(3.0);
 
+        let editable = !is_processing && selected_index.is_some();
+        render_trailers(ui, new_message, trailer_form, editable, range_len, &mut result);
+
+        egui::CollapsingHeader::new(egui::RichText::new("👤 Author / Committer / Dates").size(12.0))
+            .show(ui, |ui| {
+                let editable = !is_processing && selected_index.is_some();
+
+                egui::Grid::new("identity_grid")
+                    .num_columns(4)
+                    .spacing([6.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Author:");
+                        ui.add_enabled(
+                            editable,
+                            egui::TextEdit::singleline(&mut identity.author.name)
+                                .hint_text("Name")
+                                .desired_width(160.0),
+                        );
+                        ui.add_enabled(
+                            editable,
+                            egui::TextEdit::singleline(&mut identity.author.email)
+                                .hint_text("Email")
+                                .desired_width(220.0),
+                        );
+                        ui.add_enabled(
+                            editable,
+                            egui::TextEdit::singleline(&mut identity.author_date)
+                                .hint_text("YYYY-MM-DD HH:MM:SS +HHMM")
+                                .desired_width(190.0),
+                        );
+                        ui.end_row();
+
+                        ui.label("Committer:");
+                        ui.add_enabled(
+                            editable,
+                            egui::TextEdit::singleline(&mut identity.committer.name)
+                                .hint_text("Name")
+                                .desired_width(160.0),
+                        );
+                        ui.add_enabled(
+                            editable,
+                            egui::TextEdit::singleline(&mut identity.committer.email)
+                                .hint_text("Email")
+                                .desired_width(220.0),
+                        );
+                        ui.add_enabled(
+                            editable,
+                            egui::TextEdit::singleline(&mut identity.committer_date)
+                                .hint_text("YYYY-MM-DD HH:MM:SS +HHMM")
+                                .desired_width(190.0),
+                        );
+                        ui.end_row();
+                    });
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(editable, egui::Button::new("⇩ Same as author").small())
+                        .on_hover_text("Copy the author fields into the committer fields")
+                        .clicked()
+                    {
+                        identity.committer = identity.author.clone();
+                    }
+
+                    let range_btn = egui::Button::new(format!(
+                        "👥 Queue for selected range ({} commits)",
+                        range_len
+                    ))
+                    .small();
+
+                    if ui
+                        .add_enabled(editable && range_len > 1, range_btn)
+                        .on_hover_text(
+                            "Apply the changed fields to every commit in the range (Shift+click to select one)",
+                        )
+                        .clicked()
+                    {
+                        result.apply_identity_to_range = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("🕒 Shift dates by");
+                    ui.add_enabled(
+                        editable,
+                        egui::TextEdit::singleline(&mut identity.shift)
+                            .hint_text("+2h, -30m, 1d")
+                            .desired_width(90.0),
+                    );
+                    ui.checkbox(&mut identity.shift_author, "author");
+                    ui.checkbox(&mut identity.shift_committer, "committer");
+
+                    let shift_btn =
+                        egui::Button::new(format!("Queue shift ({} commits)", range_len)).small();
+
+                    if ui
+                        .add_enabled(
+                            editable
+                                && !identity.shift.trim().is_empty()
+                                && (identity.shift_author || identity.shift_committer),
+                            shift_btn,
+                        )
+                        .on_hover_text("Move the dates of the selected commits, keeping their UTC offsets")
+                        .clicked()
+                    {
+                        result.shift_dates_clicked = true;
+                    }
+                });
+            });
+
+        ui.add_space(3.0);
+
         ui.horizontal(|ui| {
-            let apply_btn = egui::Button::new(egui::RichText::new("✅ Apply Change").size(14.0))
+            let can_edit =
+                !is_processing && selected_index.is_some() && !new_message.trim().is_empty();
+
+            let apply_label = if pending_edits.is_empty() {
+                "✅ Apply Change"
+            } else {
+                "✅ Apply All Changes"
+            };
+            let apply_btn = egui::Button::new(egui::RichText::new(apply_label).size(14.0))
                 .min_size(egui::vec2(180.0, 36.0));
 
             if ui
                 .add_enabled(
-                    !is_processing && selected_index.is_some() && !new_message.trim().is_empty(),
+                    can_edit || (!is_processing && !pending_edits.is_empty()),
                     apply_btn,
                 )
+                .on_hover_text("Rewrite all queued commits in a single pass")
                 .clicked()
             {
                 result.apply_clicked = true;
             }
 
+            let queue_btn = egui::Button::new(egui::RichText::new("➕ Queue Edit").size(14.0))
+                .min_size(egui::vec2(140.0, 36.0));
+
+            if ui
+                .add_enabled(can_edit, queue_btn)
+                .on_hover_text("Keep this edit and select another commit to reword")
+                .clicked()
+            {
+                result.queue_clicked = true;
+            }
+
+            let squash_btn = egui::Button::new(
+                egui::RichText::new(format!("🧩 Squash ({})", range_len)).size(14.0),
+            )
+            .min_size(egui::vec2(110.0, 36.0));
+
+            if ui
+                .add_enabled(!is_processing && range_len > 1, squash_btn)
+                .on_hover_text(
+                    "Fold the selected adjacent commits into the oldest one, keeping the newest tree",
+                )
+                .clicked()
+            {
+                result.squash_clicked = true;
+            }
+
             if selected_index.is_none() {
                 ui.label(
                     egui::RichText::new("← Select a commit from the list")
@@ -81,7 +292,182 @@ pub fn render_editor_panel(
                 );
             }
         });
+
+        if !pending_edits.is_empty() {
+            ui.add_space(3.0);
+            egui::CollapsingHeader::new(
+                egui::RichText::new(format!("📦 Queued edits ({})", pending_edits.len()))
+                    .size(12.0),
+            )
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_source("queued_edits")
+                    .max_height(90.0)
+                    .show(ui, |ui| {
+                        for (i, edit) in pending_edits.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .add_enabled(!is_processing, egui::Button::new("✖").small())
+                                    .on_hover_text("Remove from queue")
+                                    .clicked()
+                                {
+                                    result.removed_edit = Some(i);
+                                }
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{} │ {}",
+                                        edit.short_hash,
+                                        edit.summary()
+                                    ))
+                                    .monospace()
+                                    .size(11.0),
+                                );
+                            });
+                        }
+                    });
+
+                if ui
+                    .add_enabled(!is_processing, egui::Button::new("🗑️ Clear queue").small())
+                    .clicked()
+                {
+                    result.clear_queue_clicked = true;
+                }
+            });
+        }
     });
 
     result
 }
+
+fn render_trailers(
+    ui: &mut egui::Ui,
+    new_message: &mut String,
+    form: &mut TrailerForm,
+    editable: bool,
+    range_len: usize,
+    result: &mut EditorPanelResult,
+) {
+    let (body, mut trailers) = split_trailers(new_message);
+    let mut changed = false;
+
+    egui::CollapsingHeader::new(
+        egui::RichText::new(format!("🏷 Trailers ({})", trailers.len())).size(12.0),
+    )
+    .show(ui, |ui| {
+        let mut move_up = None;
+        let mut remove = None;
+
+        for (i, trailer) in trailers.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(editable && i > 0, egui::Button::new("⬆").small())
+                    .on_hover_text("Move up")
+                    .clicked()
+                {
+                    move_up = Some(i);
+                }
+                if ui
+                    .add_enabled(
+                        editable && i + 1 < trailers.len(),
+                        egui::Button::new("⬇").small(),
+                    )
+                    .on_hover_text("Move down")
+                    .clicked()
+                {
+                    move_up = Some(i + 1);
+                }
+                if ui
+                    .add_enabled(editable, egui::Button::new("✖").small())
+                    .on_hover_text("Remove trailer")
+                    .clicked()
+                {
+                    remove = Some(i);
+                }
+
+                let text = egui::RichText::new(trailer.to_string())
+                    .monospace()
+                    .size(11.0);
+                ui.label(if trailer.is_trailer() {
+                    text
+                } else {
+                    text.italics().color(egui::Color32::GRAY)
+                });
+            });
+        }
+
+        if let Some(i) = move_up {
+            trailers.swap(i - 1, i);
+            changed = true;
+        }
+        if let Some(i) = remove {
+            trailers.remove(i);
+            changed = true;
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("trailer_key")
+                .selected_text(form.key.as_str())
+                .width(130.0)
+                .show_ui(ui, |ui| {
+                    for preset in TRAILER_PRESETS {
+                        ui.selectable_value(&mut form.key, preset.to_string(), preset);
+                    }
+                });
+            ui.add_enabled(
+                editable,
+                egui::TextEdit::singleline(&mut form.key)
+                    .hint_text("Custom-Key")
+                    .desired_width(110.0),
+            );
+            ui.add_enabled(
+                editable,
+                egui::TextEdit::singleline(&mut form.value)
+                    .hint_text("Name <email> or value")
+                    .desired_width(220.0),
+            );
+
+            let valid_key = !form.key.trim().is_empty()
+                && form
+                    .key
+                    .trim()
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-');
+            if ui
+                .add_enabled(
+                    editable && valid_key && !form.value.trim().is_empty(),
+                    egui::Button::new("➕ Add").small(),
+                )
+                .clicked()
+            {
+                trailers.push(Trailer::new(&form.key, &form.value));
+                form.value.clear();
+                changed = true;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(editable, egui::Button::new("✍ Sign off").small())
+                .on_hover_text("Add Signed-off-by for user.name / user.email")
+                .clicked()
+            {
+                result.signoff_clicked = true;
+            }
+
+            let range_btn =
+                egui::Button::new(format!("✍ Sign off range ({} commits)", range_len)).small();
+            if ui
+                .add_enabled(editable && range_len > 1, range_btn)
+                .on_hover_text("Queue a Signed-off-by on every commit in the selected range")
+                .clicked()
+            {
+                result.signoff_range_clicked = true;
+            }
+        });
+    });
+
+    if changed {
+        *new_message = join_trailers(&body, &trailers);
+    }
+}
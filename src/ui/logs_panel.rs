@@ -3,7 +3,7 @@ use eframe::egui;
 
 pub fn render_logs_panel(
     ui: &mut egui::Ui,
-    logs: &[LogEntry],
+    logs: &mut Vec<LogEntry>,
     log_filter: &mut LogFilter,
     show_progress: bool,
     progress: f32,
@@ -14,7 +14,9 @@ pub fn render_logs_panel(
             ui.heading(egui::RichText::new("📝 Logs").size(18.0).strong());
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui.button("🗑️ Clear").clicked() && !is_processing {}
+                if ui.button("🗑️ Clear").clicked() && !is_processing {
+                    logs.clear();
+                }
 
                 egui::ComboBox::from_id_source("log_filter")
                     .selected_text(log_filter.name())
@@ -87,7 +89,7 @@ pub fn render_logs_panel(
                         });
                     });
                 } else {
-                    for entry in logs {
+                    for entry in logs.iter() {
                         if log_filter.should_show(&entry.level) {
                             render_log_entry(ui, entry);
                         }
@@ -1,4 +1,4 @@
-use crate::models::CommitInfo;
+use crate::models::{CommitInfo, RewriteOptions};
 use eframe::egui;
 
 pub struct MainWindowResult {
@@ -6,6 +6,7 @@ pub struct MainWindowResult {
     pub refresh_clicked: bool,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_main_window(
     ui: &mut egui::Ui,
     repo_path: Option<&std::path::PathBuf>,
@@ -13,6 +14,7 @@ pub fn render_main_window(
     is_processing: bool,
     show_help: &mut bool,
     modify_all_branches: &mut bool,
+    rewrite_options: &mut RewriteOptions,
     current_branch: Option<&String>,
 ) -> MainWindowResult {
     let mut result = MainWindowResult {
@@ -67,9 +69,21 @@ pub fn render_main_window(
                     );
                     ui.label("1. Select a Git repository");
                     ui.label("2. Click on a commit in the list");
-                    ui.label("3. Edit the message");
-                    ui.label("4. Click 'Apply change'");
+                    ui.label("3. Edit the message, author or committer");
+                    ui.label("4. Click 'Apply change' (or 'Queue edit' to reword several commits)");
                     ui.label("5. Watch the logs on the right");
+                    ui.label("Tip: Shift+click selects a range for bulk identity changes or squashing");
+                    ui.label("Tip: the Trailers section adds sign-offs and co-authors");
+                    ui.label("Tip: Find & Replace previews regex edits before queueing them");
+                    ui.label("Tip: ⬆/⬇ above the list reorder commits and 🗑 drops them; conflicts are refused");
+                    ui.label("Tip: Purge removes files from every commit; Cancel in the preview restores the branches");
+                    ui.label("Tip: Large blobs scans history and strips files by size, like BFG");
+                    ui.label("Tip: Replace text in files scrubs passwords from every version, BFG --replace-text style");
+                    ui.label("Tip: Secret scan findings can be sent to Purge or Replace text in files");
+                    ui.label("Tip: Refs to rewrite picks branches, tags, remotes, notes or stash by hand or by pattern");
+                    ui.label("Tip: tags on rewritten commits move with them; annotated tags are recreated");
+                    ui.label("Tip: every rewrite is saved to Undo history; restore any earlier one from there");
+                    ui.label("Tip: ☁ marks commits already on a remote; after confirming, rewritten branches can be force-pushed with lease");
 
                     ui.add_space(3.0);
                     ui.label(
@@ -171,6 +185,60 @@ pub fn render_main_window(
                 }
             });
 
+            ui.horizontal(|ui| {
+                ui.checkbox(
+                    &mut rewrite_options.resign_commits,
+                    egui::RichText::new("🔏 Re-sign rewritten commits").size(12.0),
+                )
+                .on_hover_text(
+                    "Sign recreated commits with user.signingkey (gpg.format openpgp, x509 or ssh). \
+                     Without this, rewritten commits lose their signatures.",
+                );
+
+                ui.checkbox(
+                    &mut rewrite_options.rewrite_tags,
+                    egui::RichText::new("🏷 Move tags").size(12.0),
+                )
+                .on_hover_text(
+                    "Point tags on rewritten commits at the new commits; annotated tags keep \
+                     their message and tagger",
+                );
+                ui.add_enabled(
+                    rewrite_options.rewrite_tags,
+                    egui::Checkbox::new(
+                        &mut rewrite_options.resign_tags,
+                        egui::RichText::new("Re-sign signed tags").size(12.0),
+                    ),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                ui.checkbox(
+                    &mut rewrite_options.copy_notes,
+                    egui::RichText::new("📝 Copy notes").size(12.0),
+                )
+                .on_hover_text(
+                    "Copy git notes to the rewritten commits, like notes.rewriteRef does for rebase",
+                );
+                ui.add_enabled(
+                    rewrite_options.copy_notes,
+                    egui::TextEdit::singleline(&mut rewrite_options.notes_refs)
+                        .hint_text("all of refs/notes/*")
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(180.0),
+                )
+                .on_hover_text("Space-separated notes refs or globs, e.g. commits ci/*");
+            });
+
+            ui.checkbox(
+                &mut rewrite_options.update_hash_refs,
+                egui::RichText::new("#️⃣ Update hashes in messages").size(12.0),
+            )
+            .on_hover_text(
+                "Replace full or abbreviated hashes of rewritten commits quoted in later \
+                 messages, e.g. \"This reverts commit …\", with the new hashes",
+            );
+
             ui.add_space(2.0);
         }
 
@@ -1,5 +1,15 @@
+pub mod backups_panel;
+pub mod blobs_panel;
 pub mod commits_list;
 pub mod editor_panel;
 pub mod logs_panel;
 pub mod main_window;
+pub mod preflight_modal;
 pub mod preview_modal;
+pub mod purge_panel;
+pub mod push_modal;
+pub mod recovery_modal;
+pub mod refs_panel;
+pub mod replace_panel;
+pub mod scrub_panel;
+pub mod secrets_panel;
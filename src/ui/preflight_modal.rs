@@ -0,0 +1,89 @@
+use crate::models::PreflightReport;
+use eframe::egui;
+
+pub struct PreflightModalResult {
+    pub proceed_clicked: bool,
+    pub cancel_clicked: bool,
+}
+
+pub fn render_preflight_modal(
+    ctx: &egui::Context,
+    report: &PreflightReport,
+    acknowledged: &mut bool,
+) -> PreflightModalResult {
+    let mut result = PreflightModalResult {
+        proceed_clicked: false,
+        cancel_clicked: false,
+    };
+
+    let title = if report.is_blocked() {
+        "⛔ Can't rewrite yet"
+    } else {
+        "⚠ Before rewriting"
+    };
+    egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            for error in &report.errors {
+                ui.label(
+                    egui::RichText::new(format!("❌ {}", error))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(255, 100, 100)),
+                );
+            }
+            for warning in &report.warnings {
+                ui.label(
+                    egui::RichText::new(format!("⚠ {}", warning))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(255, 180, 50)),
+                );
+            }
+
+            if !report.diverging.is_empty() {
+                ui.add_space(5.0);
+                ui.label(
+                    egui::RichText::new(
+                        "☁ Already pushed. These remote branches will diverge from the rewritten history:",
+                    )
+                    .size(12.0)
+                    .strong()
+                    .color(egui::Color32::from_rgb(255, 180, 50)),
+                );
+                for branch in &report.diverging {
+                    ui.label(egui::RichText::new(format!("   {}", branch)).monospace().size(11.0));
+                }
+                ui.checkbox(
+                    acknowledged,
+                    "I'll force-push, and everyone who pulled these branches has to reset",
+                );
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if report.is_blocked() {
+                    if ui.button("Close").clicked() {
+                        result.cancel_clicked = true;
+                    }
+                    return;
+                }
+                let confirmed = report.diverging.is_empty() || *acknowledged;
+                if ui
+                    .add_enabled(
+                        confirmed,
+                        egui::Button::new("Rewrite anyway")
+                            .fill(egui::Color32::from_rgb(200, 140, 60)),
+                    )
+                    .clicked()
+                {
+                    result.proceed_clicked = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    result.cancel_clicked = true;
+                }
+            });
+        });
+
+    result
+}
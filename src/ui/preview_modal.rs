@@ -1,9 +1,11 @@
-use crate::models::PreviewData;
+use crate::git::commit_map::MapFormat;
+use crate::models::{format_size, CommitChange, PreviewData};
 use eframe::egui;
 
 pub struct PreviewModalResult {
     pub confirm_clicked: bool,
     pub cancel_clicked: bool,
+    pub export_map: Option<MapFormat>,
 }
 
 pub fn render_preview_modal(
@@ -14,6 +16,7 @@ pub fn render_preview_modal(
     let mut result = PreviewModalResult {
         confirm_clicked: false,
         cancel_clicked: false,
+        export_map: None,
     };
 
     egui::Window::new("🔍 Preview Changes")
@@ -33,53 +36,234 @@ pub fn render_preview_modal(
                 ui.add_space(5.0);
 
                 ui.horizontal(|ui| {
-                    ui.label("📌 Commit hash:");
+                    ui.label("📌 Edited commits:");
                     ui.label(
-                        egui::RichText::new(&preview_data.commit_hash[..8])
-                            .monospace()
+                        egui::RichText::new(format!("{}", preview_data.changes.len()))
+                            .strong()
                             .size(12.0),
                     );
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "({} commits recreated)",
+                            preview_data.commit_map.len()
+                        ))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY),
+                    );
                 });
 
-                ui.add_space(5.0);
+                if let Some(key) = &preview_data.signed_with {
+                    ui.label(
+                        egui::RichText::new(format!("🔏 Rewritten commits signed with {}", key))
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(100, 200, 100)),
+                    );
+                }
 
-                ui.label(
-                    egui::RichText::new("❌ Old message:")
-                        .size(13.0)
-                        .strong()
-                        .color(egui::Color32::from_rgb(255, 100, 100)),
-                );
-                egui::Frame::none()
-                    .fill(egui::Color32::from_rgb(40, 30, 30))
-                    .rounding(3.0)
-                    .inner_margin(8.0)
+                if !preview_data.lost_signatures.is_empty() {
+                    ui.add_space(3.0);
+                    egui::Frame::none()
+                        .fill(egui::Color32::from_rgb(80, 60, 30))
+                        .rounding(5.0)
+                        .inner_margin(6.0)
+                        .show(ui, |ui| {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "⚠️ {} signed commit(s) will lose their signature:",
+                                    preview_data.lost_signatures.len()
+                                ))
+                                .strong()
+                                .color(egui::Color32::from_rgb(255, 180, 50)),
+                            );
+                            ui.label(
+                                egui::RichText::new(preview_data.lost_signatures.join(" "))
+                                    .monospace()
+                                    .size(11.0),
+                            );
+                            ui.label(
+                                egui::RichText::new(
+                                    "Cancel and enable \"Re-sign rewritten commits\" to keep them signed.",
+                                )
+                                .size(10.0)
+                                .italics(),
+                            );
+                        });
+                }
+
+                if !preview_data.reordered.is_empty() {
+                    ui.add_space(3.0);
+                    ui.label(
+                        egui::RichText::new("↕️ New order (oldest first):")
+                            .size(12.0)
+                            .strong(),
+                    );
+                    for line in &preview_data.reordered {
+                        ui.label(egui::RichText::new(line).monospace().size(11.0));
+                    }
+                }
+
+                if !preview_data.moved_tags.is_empty() {
+                    ui.add_space(3.0);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "🏷 Tags that will move ({}):",
+                            preview_data.moved_tags.len()
+                        ))
+                        .size(12.0)
+                        .strong(),
+                    );
+                    for line in &preview_data.moved_tags {
+                        ui.label(egui::RichText::new(line).monospace().size(11.0));
+                    }
+                }
+
+                if !preview_data.hash_refs.is_empty() {
+                    ui.add_space(3.0);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "#️⃣ Hashes updated in messages ({}):",
+                            preview_data.hash_refs.len()
+                        ))
+                        .size(12.0)
+                        .strong(),
+                    );
+                    for line in &preview_data.hash_refs {
+                        ui.label(egui::RichText::new(line).monospace().size(11.0));
+                    }
+                }
+
+                if !preview_data.copied_notes.is_empty() {
+                    ui.add_space(3.0);
+                    let total: usize = preview_data.copied_notes.iter().map(|(_, n)| n).sum();
+                    let refs: Vec<String> = preview_data
+                        .copied_notes
+                        .iter()
+                        .map(|(name, n)| format!("{} ({})", name, n))
+                        .collect();
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "📝 Migrated {} note(s): {}",
+                            total,
+                            refs.join(", ")
+                        ))
+                        .size(12.0)
+                        .strong(),
+                    );
+                }
+
+                if !preview_data.purged_paths.is_empty() {
+                    ui.add_space(3.0);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "🧹 Purged from every commit: {}",
+                            preview_data.purged_paths.join(", ")
+                        ))
+                        .size(12.0)
+                        .strong(),
+                    );
+                }
+
+                if preview_data.stripped_blobs > 0 {
+                    ui.add_space(3.0);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "📦 Stripped {} blob(s): up to {} smaller once backups are removed and git gc runs",
+                            preview_data.stripped_blobs,
+                            format_size(preview_data.stripped_bytes)
+                        ))
+                        .size(12.0)
+                        .strong(),
+                    );
+                }
+
+                if preview_data.text_rules > 0 {
+                    ui.add_space(3.0);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "🔒 {} text rule(s) changed {} file version(s)",
+                            preview_data.text_rules, preview_data.replaced_blobs
+                        ))
+                        .size(12.0)
+                        .strong(),
+                    );
+                }
+
+                if !preview_data.pruned.is_empty() {
+                    egui::CollapsingHeader::new(
+                        egui::RichText::new(format!(
+                            "✂ Pruned empty commits ({})",
+                            preview_data.pruned.len()
+                        ))
+                        .size(12.0),
+                    )
                     .show(ui, |ui| {
-                        ui.label(
-                            egui::RichText::new(&preview_data.old_message)
-                                .monospace()
-                                .size(11.0),
-                        );
+                        egui::ScrollArea::vertical()
+                            .id_source("preview_pruned")
+                            .max_height(120.0)
+                            .show(ui, |ui| {
+                                for line in &preview_data.pruned {
+                                    ui.label(
+                                        egui::RichText::new(line).monospace().size(10.0),
+                                    );
+                                }
+                            });
                     });
+                }
 
                 ui.add_space(5.0);
 
-                ui.label(
-                    egui::RichText::new("✅ New message:")
-                        .size(13.0)
-                        .strong()
-                        .color(egui::Color32::from_rgb(100, 200, 100)),
-                );
-                egui::Frame::none()
-                    .fill(egui::Color32::from_rgb(30, 40, 30))
-                    .rounding(3.0)
-                    .inner_margin(8.0)
+                egui::ScrollArea::vertical()
+                    .id_source("preview_changes")
+                    .max_height(260.0)
                     .show(ui, |ui| {
-                        ui.label(
-                            egui::RichText::new(&preview_data.new_message)
-                                .monospace()
-                                .size(11.0),
-                        );
+                        for change in &preview_data.changes {
+                            render_commit_change(ui, change);
+                            ui.add_space(6.0);
+                        }
+                    });
+
+                if !preview_data.commit_map.is_empty() {
+                    egui::CollapsingHeader::new(
+                        egui::RichText::new(format!(
+                            "🔁 New hashes ({})",
+                            preview_data.commit_map.len()
+                        ))
+                        .size(12.0),
+                    )
+                    .show(ui, |ui| {
+                        if preview_data.backup.is_some() {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new("Export old → new map:")
+                                        .size(11.0)
+                                        .color(egui::Color32::GRAY),
+                                );
+                                for format in [MapFormat::Tsv, MapFormat::Json] {
+                                    let label = format!("💾 {}", format.extension().to_uppercase());
+                                    if ui.small_button(label).clicked() {
+                                        result.export_map = Some(format);
+                                    }
+                                }
+                            });
+                        }
+                        egui::ScrollArea::vertical()
+                            .id_source("preview_commit_map")
+                            .max_height(120.0)
+                            .show(ui, |ui| {
+                                for (old, new) in &preview_data.commit_map {
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "{} → {}",
+                                            &old[..8.min(old.len())],
+                                            &new[..8.min(new.len())]
+                                        ))
+                                        .monospace()
+                                        .size(10.0),
+                                    );
+                                }
+                            });
                     });
+                }
 
                 ui.add_space(10.0);
                 ui.separator();
@@ -158,3 +342,131 @@ pub fn render_preview_modal(
 
     result
 }
+
+fn render_commit_change(ui: &mut egui::Ui, change: &CommitChange) {
+    ui.label(
+        egui::RichText::new(format!("🔹 {}", &change.commit_hash[..8]))
+            .monospace()
+            .size(12.0)
+            .strong(),
+    );
+
+    if change.dropped {
+        ui.label(
+            egui::RichText::new(format!(
+                "🗑 Dropped: {}",
+                change.old_message.lines().next().unwrap_or("")
+            ))
+            .size(11.0)
+            .color(egui::Color32::from_rgb(255, 120, 120)),
+        );
+        return;
+    }
+
+    if !change.squashed.is_empty() {
+        ui.label(
+            egui::RichText::new(format!(
+                "🧩 Squashes {} commit(s): {}",
+                change.squashed.len(),
+                change.squashed.join(" ")
+            ))
+            .size(11.0)
+            .color(egui::Color32::from_rgb(150, 200, 255)),
+        );
+    }
+
+    if change.author_changed() {
+        render_field_change(ui, "👤 Author:", &change.old_author, &change.new_author);
+    }
+    if change.committer_changed() {
+        render_field_change(
+            ui,
+            "👤 Committer:",
+            &change.old_committer,
+            &change.new_committer,
+        );
+    }
+    if change.author_date_changed() {
+        render_field_change(
+            ui,
+            "🕒 Author date:",
+            &change.old_author_date,
+            &change.new_author_date,
+        );
+    }
+    if change.committer_date_changed() {
+        render_field_change(
+            ui,
+            "🕒 Committer date:",
+            &change.old_committer_date,
+            &change.new_committer_date,
+        );
+    }
+
+    if !change.message_changed() {
+        ui.label(
+            egui::RichText::new("Message unchanged")
+                .size(11.0)
+                .italics()
+                .color(egui::Color32::GRAY),
+        );
+        return;
+    }
+
+    ui.label(
+        egui::RichText::new("❌ Old message:")
+            .size(13.0)
+            .strong()
+            .color(egui::Color32::from_rgb(255, 100, 100)),
+    );
+    egui::Frame::none()
+        .fill(egui::Color32::from_rgb(40, 30, 30))
+        .rounding(3.0)
+        .inner_margin(8.0)
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(&change.old_message)
+                    .monospace()
+                    .size(11.0),
+            );
+        });
+
+    ui.add_space(5.0);
+
+    ui.label(
+        egui::RichText::new("✅ New message:")
+            .size(13.0)
+            .strong()
+            .color(egui::Color32::from_rgb(100, 200, 100)),
+    );
+    egui::Frame::none()
+        .fill(egui::Color32::from_rgb(30, 40, 30))
+        .rounding(3.0)
+        .inner_margin(8.0)
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(&change.new_message)
+                    .monospace()
+                    .size(11.0),
+            );
+        });
+}
+
+fn render_field_change(ui: &mut egui::Ui, label: &str, old: &str, new: &str) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label(egui::RichText::new(label).size(12.0).strong());
+        ui.label(
+            egui::RichText::new(old)
+                .monospace()
+                .size(11.0)
+                .color(egui::Color32::from_rgb(255, 100, 100)),
+        );
+        ui.label("→");
+        ui.label(
+            egui::RichText::new(new)
+                .monospace()
+                .size(11.0)
+                .color(egui::Color32::from_rgb(100, 200, 100)),
+        );
+    });
+}
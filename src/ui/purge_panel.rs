@@ -0,0 +1,73 @@
+use eframe::egui;
+
+#[derive(Clone, Debug, Default)]
+pub struct PurgeForm {
+    /// One path or glob pattern per line.
+    pub patterns: String,
+    pub prune_empty: bool,
+}
+
+impl PurgeForm {
+    pub fn patterns(&self) -> Vec<String> {
+        self.patterns
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+pub struct PurgePanelResult {
+    pub purge_clicked: bool,
+}
+
+pub fn render_purge_panel(
+    ui: &mut egui::Ui,
+    form: &mut PurgeForm,
+    is_processing: bool,
+) -> PurgePanelResult {
+    let mut result = PurgePanelResult {
+        purge_clicked: false,
+    };
+
+    egui::CollapsingHeader::new(egui::RichText::new("🧹 Purge paths from history").size(12.0))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(
+                    "One per line. A name like .env matches at any depth; \
+                     a path like config/keys/ matches from the repository root.",
+                )
+                .size(10.0)
+                .color(egui::Color32::GRAY),
+            );
+            ui.add_enabled(
+                !is_processing,
+                egui::TextEdit::multiline(&mut form.patterns)
+                    .hint_text("secrets.json\n*.pem\nbuild/")
+                    .font(egui::TextStyle::Monospace)
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY),
+            );
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut form.prune_empty, "Prune commits left empty");
+
+                if ui
+                    .add_enabled(
+                        !is_processing && !form.patterns().is_empty(),
+                        egui::Button::new("🧹 Purge").small(),
+                    )
+                    .on_hover_text(
+                        "Remove these paths from every commit on the selected branches; \
+                         review the preview before confirming",
+                    )
+                    .clicked()
+                {
+                    result.purge_clicked = true;
+                }
+            });
+        });
+
+    result
+}
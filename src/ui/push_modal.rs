@@ -0,0 +1,116 @@
+use crate::models::{PushResult, PushTarget};
+use eframe::egui;
+
+/// Branches offered for a force-push after a confirmed rewrite, and the
+/// remote's answer once pushed.
+pub struct PushForm {
+    pub targets: Vec<PushTarget>,
+    pub results: Option<Vec<PushResult>>,
+}
+
+pub struct PushModalResult {
+    pub push_clicked: bool,
+    pub close_clicked: bool,
+}
+
+pub fn render_push_modal(
+    ctx: &egui::Context,
+    form: &PushForm,
+    is_processing: bool,
+) -> PushModalResult {
+    let mut result = PushModalResult {
+        push_clicked: false,
+        close_clicked: false,
+    };
+
+    egui::Window::new("⬆ Push rewritten branches")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(
+                    "Each branch is force-pushed only if the remote still has the commit \
+                     it had before the rewrite.",
+                )
+                .size(12.0),
+            );
+            ui.add_space(5.0);
+
+            egui::Grid::new("push_targets_grid")
+                .num_columns(4)
+                .spacing([10.0, 2.0])
+                .show(ui, |ui| {
+                    for target in &form.targets {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{} → {}",
+                                target.branch.trim_start_matches("refs/heads/"),
+                                target.remote_name()
+                            ))
+                            .monospace()
+                            .size(11.0),
+                        );
+                        ui.label(
+                            egui::RichText::new(&target.lease[..8])
+                                .monospace()
+                                .size(11.0)
+                                .color(egui::Color32::GRAY),
+                        );
+                        ui.label(
+                            egui::RichText::new(format!("→ {}", &target.new[..8]))
+                                .monospace()
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(100, 200, 100)),
+                        );
+                        let pushed = form
+                            .results
+                            .iter()
+                            .flatten()
+                            .find(|pushed| &pushed.target == target);
+                        match pushed {
+                            Some(pushed) => {
+                                let color = if pushed.accepted {
+                                    egui::Color32::from_rgb(100, 200, 100)
+                                } else {
+                                    egui::Color32::from_rgb(255, 100, 100)
+                                };
+                                ui.label(
+                                    egui::RichText::new(&pushed.message).size(11.0).color(color),
+                                );
+                            }
+                            None => {
+                                ui.label("");
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if form.results.is_none() {
+                    if ui
+                        .add_enabled(
+                            !is_processing,
+                            egui::Button::new("⬆ Force-push with lease")
+                                .fill(egui::Color32::from_rgb(200, 140, 60)),
+                        )
+                        .clicked()
+                    {
+                        result.push_clicked = true;
+                    }
+                    if ui
+                        .add_enabled(!is_processing, egui::Button::new("Not now"))
+                        .clicked()
+                    {
+                        result.close_clicked = true;
+                    }
+                } else if ui.button("Close").clicked() {
+                    result.close_clicked = true;
+                }
+            });
+        });
+
+    result
+}
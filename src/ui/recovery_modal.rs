@@ -0,0 +1,87 @@
+use crate::models::{Journal, JournalPhase};
+use eframe::egui;
+
+pub struct RecoveryModalResult {
+    pub complete_clicked: bool,
+    pub rollback_clicked: bool,
+}
+
+pub fn render_recovery_modal(
+    ctx: &egui::Context,
+    journal: &Journal,
+    is_processing: bool,
+) -> RecoveryModalResult {
+    let mut result = RecoveryModalResult {
+        complete_clicked: false,
+        rollback_clicked: false,
+    };
+
+    egui::Window::new("⚠ Unfinished operation")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(format!(
+                    "\"{}\", started {}, {}.",
+                    journal.operation,
+                    journal.started,
+                    journal.phase.describe()
+                ))
+                .size(12.0),
+            );
+
+            if !journal.refs.is_empty() {
+                ui.add_space(5.0);
+                egui::Grid::new("journal_refs_grid")
+                    .num_columns(3)
+                    .spacing([10.0, 2.0])
+                    .show(ui, |ui| {
+                        for entry in &journal.refs {
+                            ui.label(egui::RichText::new(&entry.name).monospace().size(11.0));
+                            ui.label(
+                                egui::RichText::new(&entry.old[..8])
+                                    .monospace()
+                                    .size(11.0)
+                                    .color(egui::Color32::GRAY),
+                            );
+                            ui.label(
+                                egui::RichText::new(format!("→ {}", &entry.new[..8]))
+                                    .monospace()
+                                    .size(11.0)
+                                    .color(egui::Color32::from_rgb(100, 200, 100)),
+                            );
+                            ui.end_row();
+                        }
+                    });
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                // with no refs recorded nothing moved, so there is nothing to complete
+                let can_complete = journal.phase != JournalPhase::Rewriting;
+                if ui
+                    .add_enabled(
+                        !is_processing && can_complete,
+                        egui::Button::new("✅ Complete").fill(egui::Color32::from_rgb(80, 200, 80)),
+                    )
+                    .on_hover_text("Move every ref to its rewritten commit")
+                    .clicked()
+                {
+                    result.complete_clicked = true;
+                }
+                if ui
+                    .add_enabled(
+                        !is_processing,
+                        egui::Button::new("↩ Roll back").fill(egui::Color32::from_rgb(200, 80, 80)),
+                    )
+                    .on_hover_text("Put every ref back where it was before the operation")
+                    .clicked()
+                {
+                    result.rollback_clicked = true;
+                }
+            });
+        });
+
+    result
+}
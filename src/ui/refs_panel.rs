@@ -0,0 +1,132 @@
+use crate::models::{RefEntry, RefSelection};
+use eframe::egui;
+use std::collections::BTreeSet;
+
+#[derive(Clone, Debug, Default)]
+pub struct RefForm {
+    /// Use the picked refs instead of the current-branch / all-branches mode.
+    pub enabled: bool,
+    pub ticked: BTreeSet<String>,
+    /// Space-separated glob patterns.
+    pub include: String,
+    pub exclude: String,
+    pub available: Vec<RefEntry>,
+}
+
+impl RefForm {
+    pub fn selection(&self) -> RefSelection {
+        let split = |patterns: &str| patterns.split_whitespace().map(str::to_string).collect();
+        RefSelection {
+            refs: self.ticked.clone(),
+            include: split(&self.include),
+            exclude: split(&self.exclude),
+        }
+    }
+
+    /// The selection to pass to a rewrite, if the picker is in use.
+    pub fn active_selection(&self) -> Option<RefSelection> {
+        self.enabled.then(|| self.selection())
+    }
+}
+
+pub fn render_refs_panel(ui: &mut egui::Ui, form: &mut RefForm, is_processing: bool) {
+    egui::CollapsingHeader::new(egui::RichText::new("🔀 Refs to rewrite").size(12.0)).show(
+        ui,
+        |ui| {
+            ui.add_enabled(
+                !is_processing,
+                egui::Checkbox::new(&mut form.enabled, "Pick refs (overrides modification mode)"),
+            );
+            if !form.enabled {
+                return;
+            }
+
+            egui::Grid::new("ref_patterns_grid")
+                .num_columns(2)
+                .spacing([6.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Include:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut form.include)
+                            .hint_text("release/* refs/tags/v*")
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(260.0),
+                    );
+                    ui.end_row();
+
+                    ui.label("Exclude:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut form.exclude)
+                            .hint_text("*-rc refs/remotes/*")
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(260.0),
+                    );
+                    ui.end_row();
+                });
+
+            let picked: BTreeSet<String> = match form.selection().resolve(&form.available) {
+                Ok(refs) => {
+                    ui.label(
+                        egui::RichText::new(format!("{} ref(s) will be rewritten", refs.len()))
+                            .size(11.0)
+                            .color(if refs.is_empty() {
+                                egui::Color32::from_rgb(255, 180, 50)
+                            } else {
+                                egui::Color32::from_rgb(100, 200, 100)
+                            }),
+                    );
+                    refs.into_iter().collect()
+                }
+                Err(e) => {
+                    ui.label(
+                        egui::RichText::new(format!("{:#}", e))
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(255, 100, 100)),
+                    );
+                    BTreeSet::new()
+                }
+            };
+
+            egui::ScrollArea::vertical()
+                .id_source("ref_picker")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    let mut current_kind = None;
+                    for entry in &form.available {
+                        if current_kind != Some(entry.kind) {
+                            current_kind = Some(entry.kind);
+                            ui.label(egui::RichText::new(entry.kind.label()).size(11.0).strong());
+                        }
+                        ui.horizontal(|ui| {
+                            let mut checked = form.ticked.contains(&entry.name);
+                            if ui.checkbox(&mut checked, "").changed() {
+                                if checked {
+                                    form.ticked.insert(entry.name.clone());
+                                } else {
+                                    form.ticked.remove(&entry.name);
+                                }
+                            }
+                            let color = if picked.contains(&entry.name) {
+                                egui::Color32::from_rgb(100, 200, 255)
+                            } else {
+                                egui::Color32::GRAY
+                            };
+                            ui.label(
+                                egui::RichText::new(&entry.short_name)
+                                    .monospace()
+                                    .size(11.0)
+                                    .color(color),
+                            )
+                            .on_hover_text(&entry.name);
+                            ui.label(
+                                egui::RichText::new(&entry.target)
+                                    .monospace()
+                                    .size(10.0)
+                                    .color(egui::Color32::GRAY),
+                            );
+                        });
+                    }
+                });
+        },
+    );
+}
@@ -0,0 +1,157 @@
+use crate::models::{MessageChange, MessageReplace, ReplaceScope};
+use eframe::egui;
+
+pub struct ReplacePanelResult {
+    pub preview_clicked: bool,
+    pub queue_clicked: bool,
+    pub discard_clicked: bool,
+}
+
+pub fn render_replace_panel(
+    ui: &mut egui::Ui,
+    form: &mut MessageReplace,
+    range_len: usize,
+    search_count: usize,
+    preview: Option<&[MessageChange]>,
+    is_processing: bool,
+) -> ReplacePanelResult {
+    let mut result = ReplacePanelResult {
+        preview_clicked: false,
+        queue_clicked: false,
+        discard_clicked: false,
+    };
+
+    egui::CollapsingHeader::new(egui::RichText::new("🔁 Find & Replace in messages").size(12.0))
+        .show(ui, |ui| {
+            egui::Grid::new("replace_grid")
+                .num_columns(2)
+                .spacing([6.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Find:");
+                    ui.add_enabled(
+                        !is_processing,
+                        egui::TextEdit::singleline(&mut form.find)
+                            .hint_text(r"Regex, e.g. \bOLD-(\d+)")
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(300.0),
+                    );
+                    ui.end_row();
+
+                    ui.label("Replace:");
+                    ui.add_enabled(
+                        !is_processing,
+                        egui::TextEdit::singleline(&mut form.replace)
+                            .hint_text("NEW-$1")
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(300.0),
+                    );
+                    ui.end_row();
+                });
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut form.case_insensitive, "Ignore case");
+                ui.separator();
+                ui.radio_value(
+                    &mut form.scope,
+                    ReplaceScope::SelectedRange,
+                    format!("Selected range ({})", range_len),
+                );
+                ui.radio_value(
+                    &mut form.scope,
+                    ReplaceScope::SearchResults,
+                    format!("Search results ({})", search_count),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        !is_processing && !form.find.is_empty(),
+                        egui::Button::new("🔍 Preview replacements").small(),
+                    )
+                    .on_hover_text("Show the before/after of every matching message")
+                    .clicked()
+                {
+                    result.preview_clicked = true;
+                }
+
+                if let Some(changes) = preview {
+                    if ui
+                        .add_enabled(
+                            !is_processing && !changes.is_empty(),
+                            egui::Button::new(format!("📦 Queue {} replacements", changes.len()))
+                                .small(),
+                        )
+                        .on_hover_text(
+                            "Add these messages to the queue; nothing is written until Apply",
+                        )
+                        .clicked()
+                    {
+                        result.queue_clicked = true;
+                    }
+                    if ui.small_button("Discard").clicked() {
+                        result.discard_clicked = true;
+                    }
+                }
+            });
+
+            if let Some(changes) = preview {
+                if changes.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No messages match")
+                            .size(11.0)
+                            .italics()
+                            .color(egui::Color32::GRAY),
+                    );
+                }
+
+                egui::ScrollArea::vertical()
+                    .id_source("replace_preview")
+                    .max_height(180.0)
+                    .show(ui, |ui| {
+                        for change in changes {
+                            render_message_change(ui, change);
+                            ui.add_space(4.0);
+                        }
+                    });
+            }
+        });
+
+    result
+}
+
+/// Shows only the lines that differ, as `-` / `+` pairs.
+fn render_message_change(ui: &mut egui::Ui, change: &MessageChange) {
+    ui.label(
+        egui::RichText::new(&change.short_hash)
+            .monospace()
+            .strong()
+            .size(11.0),
+    );
+
+    let before: Vec<&str> = change.before.lines().collect();
+    let after: Vec<&str> = change.after.lines().collect();
+    for i in 0..before.len().max(after.len()) {
+        let old = before.get(i);
+        let new = after.get(i);
+        if old == new {
+            continue;
+        }
+        if let Some(line) = old {
+            ui.label(
+                egui::RichText::new(format!("- {}", line))
+                    .monospace()
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(255, 120, 120)),
+            );
+        }
+        if let Some(line) = new {
+            ui.label(
+                egui::RichText::new(format!("+ {}", line))
+                    .monospace()
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(120, 220, 120)),
+            );
+        }
+    }
+}
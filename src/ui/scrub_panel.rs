@@ -0,0 +1,87 @@
+use crate::git::tree_filter::DEFAULT_REPLACEMENT;
+use eframe::egui;
+
+#[derive(Clone, Debug, Default)]
+pub struct ScrubForm {
+    /// One rule per line, as in BFG's `--replace-text` file.
+    pub rules: String,
+    /// Used for rules without their own `==>` replacement; empty means
+    /// `***REMOVED***`.
+    pub replacement: String,
+}
+
+impl ScrubForm {
+    pub fn rules(&self) -> Vec<String> {
+        self.rules
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn replacement(&self) -> Option<String> {
+        (!self.replacement.is_empty()).then(|| self.replacement.clone())
+    }
+}
+
+pub struct ScrubPanelResult {
+    pub scrub_clicked: bool,
+}
+
+pub fn render_scrub_panel(
+    ui: &mut egui::Ui,
+    form: &mut ScrubForm,
+    is_processing: bool,
+) -> ScrubPanelResult {
+    let mut result = ScrubPanelResult {
+        scrub_clicked: false,
+    };
+
+    egui::CollapsingHeader::new(egui::RichText::new("🔒 Replace text in files").size(12.0)).show(
+        ui,
+        |ui| {
+            ui.label(
+                egui::RichText::new(
+                    "One per line: a literal string, text==>replacement, or regex:pattern==>$1",
+                )
+                .size(10.0)
+                .color(egui::Color32::GRAY),
+            );
+            ui.add_enabled(
+                !is_processing,
+                egui::TextEdit::multiline(&mut form.rules)
+                    .hint_text("hunter2\ndb-pass-123==>changeme\nregex:AKIA[0-9A-Z]{16}==>AWS_KEY")
+                    .font(egui::TextStyle::Monospace)
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY),
+            );
+
+            ui.horizontal(|ui| {
+                ui.label("Replace with:");
+                ui.add_enabled(
+                    !is_processing,
+                    egui::TextEdit::singleline(&mut form.replacement)
+                        .hint_text(DEFAULT_REPLACEMENT)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(160.0),
+                );
+
+                if ui
+                    .add_enabled(
+                        !is_processing && !form.rules().is_empty(),
+                        egui::Button::new("🔒 Replace in history").small(),
+                    )
+                    .on_hover_text(
+                        "Rewrite every file version containing a match; binary files are skipped",
+                    )
+                    .clicked()
+                {
+                    result.scrub_clicked = true;
+                }
+            });
+        },
+    );
+
+    result
+}
@@ -0,0 +1,173 @@
+use crate::models::SecretFinding;
+use eframe::egui;
+use std::collections::BTreeSet;
+
+#[derive(Clone, Debug, Default)]
+pub struct SecretForm {
+    /// Extra regexes, one per line, scanned alongside the built-in rules.
+    pub custom_rules: String,
+    pub results: Option<Vec<SecretFinding>>,
+    /// Indices into `results`.
+    pub selected: BTreeSet<usize>,
+}
+
+impl SecretForm {
+    pub fn custom_rules(&self) -> Vec<String> {
+        self.custom_rules
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn selected_findings(&self) -> Vec<&SecretFinding> {
+        let Some(results) = &self.results else {
+            return Vec::new();
+        };
+        self.selected
+            .iter()
+            .filter_map(|i| results.get(*i))
+            .collect()
+    }
+}
+
+pub struct SecretsPanelResult {
+    pub scan_clicked: bool,
+    pub export_clicked: bool,
+    pub purge_files_clicked: bool,
+    pub scrub_text_clicked: bool,
+}
+
+pub fn render_secrets_panel(
+    ui: &mut egui::Ui,
+    form: &mut SecretForm,
+    has_repo: bool,
+    is_processing: bool,
+) -> SecretsPanelResult {
+    let mut result = SecretsPanelResult {
+        scan_clicked: false,
+        export_clicked: false,
+        purge_files_clicked: false,
+        scrub_text_clicked: false,
+    };
+
+    egui::CollapsingHeader::new(egui::RichText::new("🕵 Secret scan").size(12.0)).show(ui, |ui| {
+        ui.label(
+            egui::RichText::new(
+                "Built in: AWS keys, private keys, GitHub/GitLab tokens, high-entropy strings. \
+                 Extra regexes, one per line:",
+            )
+            .size(10.0)
+            .color(egui::Color32::GRAY),
+        );
+        ui.add_enabled(
+            !is_processing,
+            egui::TextEdit::multiline(&mut form.custom_rules)
+                .hint_text(r"internal_api_key\s*=\s*\S+")
+                .font(egui::TextStyle::Monospace)
+                .desired_rows(2)
+                .desired_width(f32::INFINITY),
+        );
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !is_processing && has_repo,
+                    egui::Button::new("🔎 Scan history").small(),
+                )
+                .on_hover_text("Check every commit message and file version on any branch")
+                .clicked()
+            {
+                result.scan_clicked = true;
+            }
+
+            let has_results = form.results.as_ref().is_some_and(|r| !r.is_empty());
+            if ui
+                .add_enabled(has_results, egui::Button::new("💾 Export JSON").small())
+                .on_hover_text("Secrets are masked in the export")
+                .clicked()
+            {
+                result.export_clicked = true;
+            }
+
+            let selected = form.selected_findings();
+            let files = selected.iter().filter(|f| f.path.is_some()).count();
+            if ui
+                .add_enabled(
+                    !is_processing && files > 0,
+                    egui::Button::new(format!("🧹 Purge {} file(s)", files)).small(),
+                )
+                .on_hover_text("Add the selected files to the purge list")
+                .clicked()
+            {
+                result.purge_files_clicked = true;
+            }
+            if ui
+                .add_enabled(
+                    !is_processing && !selected.is_empty(),
+                    egui::Button::new(format!("🔒 Scrub {} secret(s)", selected.len())).small(),
+                )
+                .on_hover_text("Add the selected secrets to the replace-text rules")
+                .clicked()
+            {
+                result.scrub_text_clicked = true;
+            }
+        });
+
+        let Some(findings) = &form.results else {
+            return;
+        };
+        if findings.is_empty() {
+            ui.label(
+                egui::RichText::new("No secrets found")
+                    .size(11.0)
+                    .italics()
+                    .color(egui::Color32::from_rgb(100, 200, 100)),
+            );
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("secret_results")
+            .max_height(220.0)
+            .show(ui, |ui| {
+                for (i, finding) in findings.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let mut checked = form.selected.contains(&i);
+                        if ui.checkbox(&mut checked, "").changed() {
+                            if checked {
+                                form.selected.insert(i);
+                            } else {
+                                form.selected.remove(&i);
+                            }
+                        }
+                        ui.label(
+                            egui::RichText::new(&finding.commit_hash[..8])
+                                .monospace()
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(150, 200, 255)),
+                        );
+                        ui.label(
+                            egui::RichText::new(finding.location())
+                                .monospace()
+                                .size(11.0),
+                        );
+                        ui.label(
+                            egui::RichText::new(&finding.rule)
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(255, 180, 50)),
+                        );
+                        ui.label(
+                            egui::RichText::new(&finding.excerpt)
+                                .monospace()
+                                .size(10.0)
+                                .color(egui::Color32::GRAY),
+                        );
+                    });
+                }
+            });
+    });
+
+    result
+}
@@ -1,4 +1,4 @@
 #[test]
 fn test_placeholder() {
-    assert!(true);
+    assert_eq!(env!("CARGO_PKG_NAME"), "commit-rewriter");
 }